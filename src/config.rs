@@ -0,0 +1,897 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Deserializer};
+
+use crate::error::ApiError;
+use crate::services::asset_filter::{
+    parse_preferences, GRID_DIMENSIONS, GRID_STYLES, HERO_DIMENSIONS, HERO_STYLES, LOGO_STYLES,
+};
+use crate::services::selection::is_valid_language;
+
+/// Environment variable naming the configuration file.
+pub const CONFIG_FILE_ENV: &str = "COSY_GAMEAPI_CONFIG";
+/// Configuration file read from the working directory if `COSY_GAMEAPI_CONFIG` is unset.
+pub const DEFAULT_CONFIG_FILE: &str = "cosy-gameapi.toml";
+
+/// Settings read once at startup.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub upstream: UpstreamConfig,
+    pub pagination: PaginationConfig,
+    pub enrichment: EnrichmentConfig,
+    pub cache: CacheConfig,
+    pub persistent_cache: PersistentCacheConfig,
+    pub retry: RetryConfig,
+    pub circuit_breaker: CircuitBreakerConfig,
+    pub rate_limit: RateLimitConfig,
+    pub health: HealthConfig,
+    pub content: ContentPolicyConfig,
+    pub log: LogConfig,
+    /// Bearer token guarding the `/admin` routes; they are disabled when unset.
+    pub admin_token: Option<String>,
+}
+
+impl Config {
+    /// Reads the TOML file named by `COSY_GAMEAPI_CONFIG` (or `cosy-gameapi.toml`, if
+    /// present), overrides it with `COSY_GAMEAPI_*` environment variables and validates
+    /// the result.
+    pub fn load() -> Result<Self, ApiError> {
+        let mut config = match std::env::var(CONFIG_FILE_ENV) {
+            Ok(path) => Self::read_file(Path::new(&path))?,
+            Err(_) if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::read_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            Err(_) => Config::default(),
+        };
+        config.apply_env()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parses and validates a TOML configuration, without environment overrides.
+    pub fn from_toml(source: &str) -> Result<Self, ApiError> {
+        let config: Config = toml::from_str(source)
+            .map_err(|e| ApiError::Config(format!("Failed to parse configuration: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn read_file(path: &Path) -> Result<Self, ApiError> {
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            ApiError::Config(format!(
+                "Failed to read config file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        toml::from_str(&source).map_err(|e| ApiError::Config(format!("{}: {}", path.display(), e)))
+    }
+
+    fn apply_env(&mut self) -> Result<(), ApiError> {
+        self.server.apply_env("COSY_GAMEAPI_SERVER")?;
+        self.upstream.apply_env("COSY_GAMEAPI_UPSTREAM")?;
+        self.pagination.apply_env("COSY_GAMEAPI_PAGINATION")?;
+        self.enrichment.apply_env("COSY_GAMEAPI_ENRICHMENT")?;
+        self.cache.apply_env("COSY_GAMEAPI_CACHE")?;
+        self.persistent_cache
+            .apply_env("COSY_GAMEAPI_PERSISTENT_CACHE")?;
+        self.retry.apply_env("COSY_GAMEAPI_RETRY")?;
+        self.circuit_breaker
+            .apply_env("COSY_GAMEAPI_CIRCUIT_BREAKER")?;
+        self.rate_limit.apply_env("COSY_GAMEAPI_RATE_LIMIT")?;
+        self.health.apply_env("COSY_GAMEAPI_HEALTH")?;
+        self.content.apply_env("COSY_GAMEAPI_CONTENT")?;
+        self.log.apply_env("COSY_GAMEAPI_LOG")?;
+        if let Ok(token) = std::env::var("COSY_GAMEAPI_ADMIN_TOKEN") {
+            self.admin_token = Some(token);
+        }
+        self.admin_token = self.admin_token.take().filter(|token| !token.is_empty());
+        Ok(())
+    }
+
+    /// Rejects settings the service cannot run with.
+    pub fn validate(&self) -> Result<(), ApiError> {
+        self.server.validate()?;
+        self.upstream.validate()?;
+        self.pagination.validate()?;
+        self.enrichment.validate()?;
+        self.cache.validate()?;
+        self.persistent_cache.validate()?;
+        self.retry.validate()?;
+        self.circuit_breaker.validate()?;
+        self.rate_limit.validate()?;
+        self.health.validate()?;
+        self.log.validate()
+    }
+}
+
+/// Where the HTTP server listens.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub host: String,
+    pub port: u16,
+    /// Number of worker threads; actix' default (one per core) when unset.
+    pub workers: Option<usize>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port: 8080,
+            workers: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    fn apply_env(&mut self, prefix: &str) -> Result<(), ApiError> {
+        env_override(&format!("{}_HOST", prefix), &mut self.host)?;
+        env_override(&format!("{}_PORT", prefix), &mut self.port)?;
+        env_override_some(&format!("{}_WORKERS", prefix), &mut self.workers)
+    }
+
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.host.trim().is_empty() {
+            return Err(invalid("server.host must not be empty"));
+        }
+        if self.workers == Some(0) {
+            return Err(invalid("server.workers must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+/// How SteamGridDB is reached.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UpstreamConfig {
+    pub base_url: String,
+    /// Timeout of a single request, including reading the body.
+    pub timeout_ms: u64,
+    /// SteamGridDB API key, usually given as `COSY_GAMEAPI_SGDB_API_KEY`.
+    pub api_key: Option<String>,
+}
+
+impl Default for UpstreamConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "https://www.steamgriddb.com/api/v2".to_string(),
+            timeout_ms: 5_000,
+            api_key: None,
+        }
+    }
+}
+
+impl UpstreamConfig {
+    fn apply_env(&mut self, prefix: &str) -> Result<(), ApiError> {
+        env_override(&format!("{}_BASE_URL", prefix), &mut self.base_url)?;
+        env_override(&format!("{}_TIMEOUT_MS", prefix), &mut self.timeout_ms)?;
+        env_override_some("COSY_GAMEAPI_SGDB_API_KEY", &mut self.api_key)?;
+        self.api_key = self.api_key.take().filter(|key| !key.is_empty());
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), ApiError> {
+        if !(self.base_url.starts_with("http://") || self.base_url.starts_with("https://")) {
+            return Err(invalid(format!(
+                "upstream.base_url must be an http(s) URL, got {:?}",
+                self.base_url
+            )));
+        }
+        if self.timeout_ms == 0 {
+            return Err(invalid("upstream.timeout_ms must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+/// Page sizes of `/games` and `/assets`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PaginationConfig {
+    /// Used when a request does not pass `limit`.
+    pub default_limit: u32,
+    /// Larger `limit`s are capped to this.
+    pub max_limit: u32,
+}
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        Self {
+            default_limit: 15,
+            max_limit: 100,
+        }
+    }
+}
+
+impl PaginationConfig {
+    fn apply_env(&mut self, prefix: &str) -> Result<(), ApiError> {
+        env_override(
+            &format!("{}_DEFAULT_LIMIT", prefix),
+            &mut self.default_limit,
+        )?;
+        env_override(&format!("{}_MAX_LIMIT", prefix), &mut self.max_limit)
+    }
+
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.default_limit == 0 {
+            return Err(invalid("pagination.default_limit must be at least 1"));
+        }
+        if self.max_limit < self.default_limit {
+            return Err(invalid(
+                "pagination.max_limit must not be smaller than pagination.default_limit",
+            ));
+        }
+        Ok(())
+    }
+
+    /// The page size to use for a requested `limit`.
+    pub fn limit(&self, requested: Option<u32>) -> u32 {
+        requested.unwrap_or(self.default_limit).min(self.max_limit)
+    }
+}
+
+/// Fetching logos, heroes, grids and icons for search results.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnrichmentConfig {
+    /// Games enriched at the same time within one request.
+    pub concurrency: usize,
+    /// Assets of a game considered when picking one of a kind for it.
+    pub candidates: u32,
+    /// What decides between candidates matching the preferences below equally well.
+    pub rank_by: Ranking,
+    /// Preferred logo styles, best first.
+    pub logo_styles: Vec<String>,
+    /// Preferred hero styles, best first.
+    pub hero_styles: Vec<String>,
+    /// Preferred hero dimensions, best first.
+    pub hero_dimensions: Vec<String>,
+    /// Preferred grid styles, best first.
+    pub grid_styles: Vec<String>,
+    /// Preferred grid dimensions, best first.
+    pub grid_dimensions: Vec<String>,
+    /// Preferred asset language, e.g. `en`.
+    pub language: Option<String>,
+    /// Pick static assets over animated ones when a game has both.
+    pub prefer_static: bool,
+}
+
+impl Default for EnrichmentConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 8,
+            candidates: 10,
+            rank_by: Ranking::default(),
+            logo_styles: Vec::new(),
+            hero_styles: Vec::new(),
+            hero_dimensions: Vec::new(),
+            grid_styles: Vec::new(),
+            grid_dimensions: Vec::new(),
+            language: None,
+            prefer_static: false,
+        }
+    }
+}
+
+impl EnrichmentConfig {
+    fn apply_env(&mut self, prefix: &str) -> Result<(), ApiError> {
+        env_override(&format!("{}_CONCURRENCY", prefix), &mut self.concurrency)?;
+        env_override(&format!("{}_CANDIDATES", prefix), &mut self.candidates)?;
+        env_override(&format!("{}_RANK_BY", prefix), &mut self.rank_by)?;
+        env_override_list(&format!("{}_LOGO_STYLES", prefix), &mut self.logo_styles);
+        env_override_list(&format!("{}_HERO_STYLES", prefix), &mut self.hero_styles);
+        env_override_list(
+            &format!("{}_HERO_DIMENSIONS", prefix),
+            &mut self.hero_dimensions,
+        );
+        env_override_list(&format!("{}_GRID_STYLES", prefix), &mut self.grid_styles);
+        env_override_list(
+            &format!("{}_GRID_DIMENSIONS", prefix),
+            &mut self.grid_dimensions,
+        );
+        env_override_some(&format!("{}_LANGUAGE", prefix), &mut self.language)?;
+        env_override(
+            &format!("{}_PREFER_STATIC", prefix),
+            &mut self.prefer_static,
+        )
+    }
+
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.concurrency == 0 {
+            return Err(invalid("enrichment.concurrency must be at least 1"));
+        }
+        if !(1..=50).contains(&self.candidates) {
+            return Err(invalid("enrichment.candidates must be between 1 and 50"));
+        }
+        for (name, values, allowed) in [
+            ("logo_styles", &self.logo_styles, LOGO_STYLES),
+            ("hero_styles", &self.hero_styles, HERO_STYLES),
+            ("hero_dimensions", &self.hero_dimensions, HERO_DIMENSIONS),
+            ("grid_styles", &self.grid_styles, GRID_STYLES),
+            ("grid_dimensions", &self.grid_dimensions, GRID_DIMENSIONS),
+        ] {
+            parse_preferences(name, &values.join(","), allowed)
+                .map_err(|e| invalid(format!("enrichment.{}: {}", name, e)))?;
+        }
+        if let Some(language) = &self.language {
+            if !is_valid_language(language) {
+                return Err(invalid(
+                    "enrichment.language must be a two or three letter code such as \"en\"",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    #[serde(deserialize_with = "CacheKindConfig::search")]
+    pub search: CacheKindConfig,
+    #[serde(deserialize_with = "CacheKindConfig::lists")]
+    pub grids: CacheKindConfig,
+    #[serde(deserialize_with = "CacheKindConfig::lists")]
+    pub hero_lists: CacheKindConfig,
+    #[serde(deserialize_with = "CacheKindConfig::lists")]
+    pub logo_lists: CacheKindConfig,
+    #[serde(deserialize_with = "CacheKindConfig::lists")]
+    pub icon_lists: CacheKindConfig,
+    #[serde(deserialize_with = "CacheKindConfig::logos")]
+    pub logos: CacheKindConfig,
+    #[serde(deserialize_with = "CacheKindConfig::heroes")]
+    pub heroes: CacheKindConfig,
+    #[serde(deserialize_with = "CacheKindConfig::icons")]
+    pub icons: CacheKindConfig,
+    /// How long a game without logo, hero or icon is remembered as such.
+    pub negative_ttl_secs: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            search: CacheKindConfig::SEARCH,
+            grids: CacheKindConfig::LISTS,
+            hero_lists: CacheKindConfig::LISTS,
+            logo_lists: CacheKindConfig::LISTS,
+            icon_lists: CacheKindConfig::LISTS,
+            logos: CacheKindConfig::LOGOS,
+            heroes: CacheKindConfig::HEROES,
+            icons: CacheKindConfig::ICONS,
+            negative_ttl_secs: 60 * 60,
+        }
+    }
+}
+
+impl CacheConfig {
+    fn apply_env(&mut self, prefix: &str) -> Result<(), ApiError> {
+        env_override(&format!("{}_ENABLED", prefix), &mut self.enabled)?;
+        env_override(
+            &format!("{}_NEGATIVE_TTL_SECS", prefix),
+            &mut self.negative_ttl_secs,
+        )?;
+        self.search.apply_env(&format!("{}_SEARCH", prefix))?;
+        self.grids.apply_env(&format!("{}_GRIDS", prefix))?;
+        self.hero_lists
+            .apply_env(&format!("{}_HERO_LISTS", prefix))?;
+        self.logo_lists
+            .apply_env(&format!("{}_LOGO_LISTS", prefix))?;
+        self.icon_lists
+            .apply_env(&format!("{}_ICON_LISTS", prefix))?;
+        self.logos.apply_env(&format!("{}_LOGOS", prefix))?;
+        self.heroes.apply_env(&format!("{}_HEROES", prefix))?;
+        self.icons.apply_env(&format!("{}_ICONS", prefix))
+    }
+
+    fn validate(&self) -> Result<(), ApiError> {
+        self.search.validate("cache.search")?;
+        self.grids.validate("cache.grids")?;
+        self.hero_lists.validate("cache.hero_lists")?;
+        self.logo_lists.validate("cache.logo_lists")?;
+        self.icon_lists.validate("cache.icon_lists")?;
+        self.logos.validate("cache.logos")?;
+        self.heroes.validate("cache.heroes")?;
+        self.icons.validate("cache.icons")
+    }
+}
+
+/// Time to live and size limit of a single cache kind.
+#[derive(Debug, Clone)]
+pub struct CacheKindConfig {
+    pub ttl_secs: u64,
+    pub max_entries: usize,
+}
+
+/// A `[cache.<kind>]` section as written; omitted fields keep the kind's defaults.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CacheKindSection {
+    ttl_secs: Option<u64>,
+    max_entries: Option<usize>,
+}
+
+impl CacheKindConfig {
+    const SEARCH: Self = Self::new(10 * 60, 1_000);
+    /// Pages of grids, heroes, logos or icons.
+    const LISTS: Self = Self::new(60 * 60, 500);
+    const LOGOS: Self = Self::new(6 * 60 * 60, 5_000);
+    const HEROES: Self = Self::new(6 * 60 * 60, 5_000);
+    const ICONS: Self = Self::new(6 * 60 * 60, 5_000);
+
+    pub const fn new(ttl_secs: u64, max_entries: usize) -> Self {
+        Self {
+            ttl_secs,
+            max_entries,
+        }
+    }
+
+    fn apply_env(&mut self, prefix: &str) -> Result<(), ApiError> {
+        env_override(&format!("{}_TTL_SECS", prefix), &mut self.ttl_secs)?;
+        env_override(&format!("{}_MAX_ENTRIES", prefix), &mut self.max_entries)
+    }
+
+    fn validate(&self, section: &str) -> Result<(), ApiError> {
+        if self.ttl_secs == 0 {
+            return Err(invalid(format!("{}.ttl_secs must be at least 1", section)));
+        }
+        if self.max_entries == 0 {
+            return Err(invalid(format!(
+                "{}.max_entries must be at least 1",
+                section
+            )));
+        }
+        Ok(())
+    }
+
+    fn with_defaults<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        defaults: Self,
+    ) -> Result<Self, D::Error> {
+        let section = CacheKindSection::deserialize(deserializer)?;
+        Ok(Self {
+            ttl_secs: section.ttl_secs.unwrap_or(defaults.ttl_secs),
+            max_entries: section.max_entries.unwrap_or(defaults.max_entries),
+        })
+    }
+
+    fn search<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::with_defaults(deserializer, Self::SEARCH)
+    }
+
+    fn lists<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::with_defaults(deserializer, Self::LISTS)
+    }
+
+    fn logos<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::with_defaults(deserializer, Self::LOGOS)
+    }
+
+    fn heroes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::with_defaults(deserializer, Self::HEROES)
+    }
+
+    fn icons<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::with_defaults(deserializer, Self::ICONS)
+    }
+}
+
+/// On-disk cache for search results and asset lists, off unless enabled.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PersistentCacheConfig {
+    pub enabled: bool,
+    pub path: PathBuf,
+    /// Age after which an entry is refreshed from SteamGridDB.
+    pub ttl_secs: u64,
+    /// How long past `ttl_secs` an entry may still be served while it is refreshed.
+    pub stale_secs: u64,
+}
+
+impl Default for PersistentCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: PathBuf::from("cosy-gameapi-cache.redb"),
+            ttl_secs: 24 * 60 * 60,
+            stale_secs: 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+impl PersistentCacheConfig {
+    fn apply_env(&mut self, prefix: &str) -> Result<(), ApiError> {
+        env_override(&format!("{}_ENABLED", prefix), &mut self.enabled)?;
+        env_override(&format!("{}_PATH", prefix), &mut self.path)?;
+        env_override(&format!("{}_TTL_SECS", prefix), &mut self.ttl_secs)?;
+        env_override(&format!("{}_STALE_SECS", prefix), &mut self.stale_secs)
+    }
+
+    fn validate(&self) -> Result<(), ApiError> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.path.as_os_str().is_empty() {
+            return Err(invalid("persistent_cache.path must not be empty"));
+        }
+        if self.ttl_secs == 0 {
+            return Err(invalid("persistent_cache.ttl_secs must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+/// Retry behaviour for idempotent upstream GETs.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RetryConfig {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Fraction (0.0 to 1.0) of each backoff that may randomly be skipped.
+    pub jitter: f64,
+    /// Upper bound for all attempts of one request together.
+    pub deadline_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 2_000,
+            jitter: 0.5,
+            deadline_ms: 10_000,
+        }
+    }
+}
+
+impl RetryConfig {
+    fn apply_env(&mut self, prefix: &str) -> Result<(), ApiError> {
+        env_override(&format!("{}_MAX_ATTEMPTS", prefix), &mut self.max_attempts)?;
+        env_override(
+            &format!("{}_INITIAL_BACKOFF_MS", prefix),
+            &mut self.initial_backoff_ms,
+        )?;
+        env_override(
+            &format!("{}_MAX_BACKOFF_MS", prefix),
+            &mut self.max_backoff_ms,
+        )?;
+        env_override(&format!("{}_JITTER", prefix), &mut self.jitter)?;
+        env_override(&format!("{}_DEADLINE_MS", prefix), &mut self.deadline_ms)
+    }
+
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.max_attempts == 0 {
+            return Err(invalid("retry.max_attempts must be at least 1"));
+        }
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err(invalid("retry.jitter must be between 0.0 and 1.0"));
+        }
+        if self.initial_backoff_ms > self.max_backoff_ms {
+            return Err(invalid(
+                "retry.initial_backoff_ms must not be greater than retry.max_backoff_ms",
+            ));
+        }
+        if self.deadline_ms == 0 {
+            return Err(invalid("retry.deadline_ms must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+/// When to stop calling SteamGridDB after repeated failures.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CircuitBreakerConfig {
+    pub enabled: bool,
+    /// Consecutive timeouts, connection errors or 5xx responses that open the circuit.
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a probe request is let through.
+    pub open_secs: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            failure_threshold: 5,
+            open_secs: 30,
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    fn apply_env(&mut self, prefix: &str) -> Result<(), ApiError> {
+        env_override(&format!("{}_ENABLED", prefix), &mut self.enabled)?;
+        env_override(
+            &format!("{}_FAILURE_THRESHOLD", prefix),
+            &mut self.failure_threshold,
+        )?;
+        env_override(&format!("{}_OPEN_SECS", prefix), &mut self.open_secs)
+    }
+
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.failure_threshold == 0 {
+            return Err(invalid(
+                "circuit_breaker.failure_threshold must be at least 1",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Outbound request rate towards SteamGridDB, shared by all requests of the process.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub requests_per_second: f64,
+    /// Requests that may be sent at once after a quiet period.
+    pub burst: u32,
+    /// Requests that may wait for the limiter before further ones are rejected.
+    pub max_queued: usize,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            requests_per_second: 10.0,
+            burst: 20,
+            max_queued: 100,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    fn apply_env(&mut self, prefix: &str) -> Result<(), ApiError> {
+        env_override(&format!("{}_ENABLED", prefix), &mut self.enabled)?;
+        env_override(
+            &format!("{}_REQUESTS_PER_SECOND", prefix),
+            &mut self.requests_per_second,
+        )?;
+        env_override(&format!("{}_BURST", prefix), &mut self.burst)?;
+        env_override(&format!("{}_MAX_QUEUED", prefix), &mut self.max_queued)
+    }
+
+    fn validate(&self) -> Result<(), ApiError> {
+        if !(self.requests_per_second >= 0.001 && self.requests_per_second.is_finite()) {
+            return Err(invalid(
+                "rate_limit.requests_per_second must be a finite number of at least 0.001",
+            ));
+        }
+        if self.burst == 0 {
+            return Err(invalid("rate_limit.burst must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+/// Readiness checks.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HealthConfig {
+    /// Minimum time between two probes of SteamGridDB triggered by `/health/ready`.
+    pub probe_interval_secs: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            probe_interval_secs: 30,
+        }
+    }
+}
+
+impl HealthConfig {
+    fn apply_env(&mut self, prefix: &str) -> Result<(), ApiError> {
+        env_override(
+            &format!("{}_PROBE_INTERVAL_SECS", prefix),
+            &mut self.probe_interval_secs,
+        )
+    }
+
+    fn validate(&self) -> Result<(), ApiError> {
+        if self.probe_interval_secs == 0 {
+            return Err(invalid("health.probe_interval_secs must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+/// What decides which asset enrichment picks among equally preferred ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ranking {
+    /// The highest SteamGridDB score.
+    #[default]
+    Score,
+    /// The most upvotes.
+    Upvotes,
+    /// The first one SteamGridDB lists.
+    First,
+}
+
+impl Ranking {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ranking::Score => "score",
+            Ranking::Upvotes => "upvotes",
+            Ranking::First => "first",
+        }
+    }
+}
+
+impl FromStr for Ranking {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "score" => Ok(Ranking::Score),
+            "upvotes" => Ok(Ranking::Upvotes),
+            "first" => Ok(Ranking::First),
+            _ => Err("expected \"score\", \"upvotes\" or \"first\"".into()),
+        }
+    }
+}
+
+/// How assets flagged as NSFW, humorous or epilepsy-triggering are treated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentRule {
+    /// Never returned; requests asking for them are rejected.
+    Block,
+    /// Left out unless a request asks for them.
+    #[default]
+    OptIn,
+    /// Returned as SteamGridDB lists them, unless a request leaves them out.
+    Allow,
+}
+
+impl FromStr for ContentRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "block" => Ok(ContentRule::Block),
+            "opt_in" => Ok(ContentRule::OptIn),
+            "allow" => Ok(ContentRule::Allow),
+            _ => Err("expected \"block\", \"opt_in\" or \"allow\"".into()),
+        }
+    }
+}
+
+/// Which flagged assets listings and enrichment return.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ContentPolicyConfig {
+    pub nsfw: ContentRule,
+    pub humor: ContentRule,
+    pub epilepsy: ContentRule,
+}
+
+impl ContentPolicyConfig {
+    fn apply_env(&mut self, prefix: &str) -> Result<(), ApiError> {
+        env_override(&format!("{}_NSFW", prefix), &mut self.nsfw)?;
+        env_override(&format!("{}_HUMOR", prefix), &mut self.humor)?;
+        env_override(&format!("{}_EPILEPSY", prefix), &mut self.epilepsy)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per line, including the fields of all enclosing spans.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err("expected \"text\" or \"json\"".into()),
+        }
+    }
+}
+
+/// Log output.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub format: LogFormat,
+    /// `tracing` filter directives, e.g. `info` or `cosy_gameapi=debug,info`.
+    pub level: String,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Text,
+            level: "info".to_string(),
+        }
+    }
+}
+
+impl LogConfig {
+    fn apply_env(&mut self, prefix: &str) -> Result<(), ApiError> {
+        env_override(&format!("{}_FORMAT", prefix), &mut self.format)?;
+        env_override(&format!("{}_LEVEL", prefix), &mut self.level)
+    }
+
+    fn validate(&self) -> Result<(), ApiError> {
+        tracing_subscriber::EnvFilter::try_new(&self.level)
+            .map(|_| ())
+            .map_err(|e| invalid(format!("log.level {:?} is invalid: {}", self.level, e)))
+    }
+}
+
+/// Replaces `target` with the parsed value of the environment variable `name`, if set.
+fn env_override<T>(name: &str, target: &mut T) -> Result<(), ApiError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let Ok(raw) = std::env::var(name) else {
+        return Ok(());
+    };
+
+    *target = raw
+        .trim()
+        .parse()
+        .map_err(|e| ApiError::Config(format!("{}={:?} is invalid: {}", name, raw, e)))?;
+
+    Ok(())
+}
+
+/// Like [`env_override`], for settings that are unset by default.
+fn env_override_some<T>(name: &str, target: &mut Option<T>) -> Result<(), ApiError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let Ok(raw) = std::env::var(name) else {
+        return Ok(());
+    };
+
+    *target = Some(
+        raw.trim()
+            .parse()
+            .map_err(|e| ApiError::Config(format!("{}={:?} is invalid: {}", name, raw, e)))?,
+    );
+
+    Ok(())
+}
+
+/// Overrides a list with the comma separated values of an environment variable.
+fn env_override_list(name: &str, target: &mut Vec<String>) {
+    if let Ok(raw) = std::env::var(name) {
+        *target = raw
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(String::from)
+            .collect();
+    }
+}
+
+fn invalid(message: impl Into<String>) -> ApiError {
+    ApiError::Config(message.into())
+}
@@ -0,0 +1,222 @@
+use std::{fmt, time::Duration};
+
+use crate::services::endpoint::Endpoint;
+
+/// Errors produced while talking to SteamGridDB or setting up the service.
+///
+/// Upstream variants carry the endpoint and, where applicable, the game id the
+/// request was made for, so they can be logged and mapped to HTTP responses.
+#[derive(Debug, Clone)]
+pub enum ApiError {
+    /// SteamGridDB answered with a non-success HTTP status.
+    UpstreamStatus {
+        endpoint: Endpoint,
+        game_id: Option<usize>,
+        status: u16,
+    },
+    /// SteamGridDB answered `429 Too Many Requests`, optionally saying when to try again.
+    UpstreamRateLimited {
+        endpoint: Endpoint,
+        game_id: Option<usize>,
+        retry_after: Option<Duration>,
+    },
+    /// The request did not complete within the configured timeout.
+    Timeout {
+        endpoint: Endpoint,
+        game_id: Option<usize>,
+    },
+    /// The request could not be sent or the response body could not be read.
+    Request {
+        endpoint: Endpoint,
+        game_id: Option<usize>,
+        message: String,
+    },
+    /// The response body was not the JSON we expected.
+    Decode {
+        endpoint: Endpoint,
+        game_id: Option<usize>,
+        message: String,
+    },
+    /// SteamGridDB answered with `success: false`.
+    Unsuccessful {
+        endpoint: Endpoint,
+        game_id: Option<usize>,
+    },
+    /// The request was not sent because the circuit breaker considers SteamGridDB down.
+    CircuitOpen {
+        endpoint: Endpoint,
+        game_id: Option<usize>,
+    },
+    /// Too many requests were already waiting for the outbound rate limiter.
+    Throttled {
+        endpoint: Endpoint,
+        game_id: Option<usize>,
+    },
+    /// The service is misconfigured (e.g. an unusable API key).
+    Config(String),
+}
+
+impl ApiError {
+    pub(crate) fn from_reqwest(
+        endpoint: Endpoint,
+        game_id: Option<usize>,
+        err: reqwest::Error,
+    ) -> Self {
+        if err.is_timeout() {
+            ApiError::Timeout { endpoint, game_id }
+        } else {
+            ApiError::Request {
+                endpoint,
+                game_id,
+                message: err.to_string(),
+            }
+        }
+    }
+
+    /// Whether repeating the request may succeed: timeouts, connection failures,
+    /// upstream 5xx and 429. Other 4xx and malformed payloads are not retried.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ApiError::Timeout { .. }
+            | ApiError::Request { .. }
+            | ApiError::UpstreamRateLimited { .. } => true,
+            ApiError::UpstreamStatus { status, .. } => *status == 429 || *status >= 500,
+            ApiError::Decode { .. }
+            | ApiError::Unsuccessful { .. }
+            | ApiError::CircuitOpen { .. }
+            | ApiError::Throttled { .. }
+            | ApiError::Config(_) => false,
+        }
+    }
+
+    /// Whether this failure suggests SteamGridDB itself is unavailable: timeouts,
+    /// connection failures and 5xx. These count towards opening the circuit breaker.
+    pub fn is_outage(&self) -> bool {
+        match self {
+            ApiError::Timeout { .. } | ApiError::Request { .. } => true,
+            ApiError::UpstreamStatus { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// How long SteamGridDB asked us to wait before the next request, if it did.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ApiError::UpstreamRateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// The upstream endpoint involved, if this is an upstream error.
+    pub fn endpoint(&self) -> Option<Endpoint> {
+        match self {
+            ApiError::UpstreamStatus { endpoint, .. }
+            | ApiError::UpstreamRateLimited { endpoint, .. }
+            | ApiError::Timeout { endpoint, .. }
+            | ApiError::Request { endpoint, .. }
+            | ApiError::Decode { endpoint, .. }
+            | ApiError::Unsuccessful { endpoint, .. }
+            | ApiError::CircuitOpen { endpoint, .. }
+            | ApiError::Throttled { endpoint, .. } => Some(*endpoint),
+            ApiError::Config(_) => None,
+        }
+    }
+
+    /// The game id the failed request was made for, if any.
+    pub fn game_id(&self) -> Option<usize> {
+        match self {
+            ApiError::UpstreamStatus { game_id, .. }
+            | ApiError::UpstreamRateLimited { game_id, .. }
+            | ApiError::Timeout { game_id, .. }
+            | ApiError::Request { game_id, .. }
+            | ApiError::Decode { game_id, .. }
+            | ApiError::Unsuccessful { game_id, .. }
+            | ApiError::CircuitOpen { game_id, .. }
+            | ApiError::Throttled { game_id, .. } => *game_id,
+            ApiError::Config(_) => None,
+        }
+    }
+}
+
+struct ForGame(Option<usize>);
+
+impl fmt::Display for ForGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(id) => write!(f, " for game {}", id),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::UpstreamStatus {
+                endpoint,
+                game_id,
+                status,
+            } => write!(
+                f,
+                "Failed to fetch {}{}: upstream responded with status {}",
+                endpoint,
+                ForGame(*game_id),
+                status
+            ),
+            ApiError::UpstreamRateLimited {
+                endpoint, game_id, ..
+            } => write!(
+                f,
+                "Failed to fetch {}{}: upstream is rate limiting our requests",
+                endpoint,
+                ForGame(*game_id)
+            ),
+            ApiError::Timeout { endpoint, game_id } => {
+                write!(f, "Timed out fetching {}{}", endpoint, ForGame(*game_id))
+            }
+            ApiError::Request {
+                endpoint,
+                game_id,
+                message,
+            } => write!(
+                f,
+                "Failed to fetch {}{}: {}",
+                endpoint,
+                ForGame(*game_id),
+                message
+            ),
+            ApiError::Decode {
+                endpoint,
+                game_id,
+                message,
+            } => write!(
+                f,
+                "Failed to decode steamgriddb {} response{}: {}",
+                endpoint,
+                ForGame(*game_id),
+                message
+            ),
+            ApiError::Unsuccessful { endpoint, game_id } => write!(
+                f,
+                "steamgriddb API returned success=false for {}{}",
+                endpoint,
+                ForGame(*game_id)
+            ),
+            ApiError::CircuitOpen { endpoint, game_id } => write!(
+                f,
+                "Not fetching {}{}: steamgriddb is unavailable (circuit breaker open)",
+                endpoint,
+                ForGame(*game_id)
+            ),
+            ApiError::Throttled { endpoint, game_id } => write!(
+                f,
+                "Not fetching {}{}: too many requests are waiting for steamgriddb",
+                endpoint,
+                ForGame(*game_id)
+            ),
+            ApiError::Config(message) => write!(f, "Invalid configuration: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
@@ -1,50 +1,126 @@
-use std::{error::Error, sync::Arc};
+use std::{sync::Arc, time::Duration};
 
 use reqwest::header::{HeaderMap, HeaderValue};
 use steamgriddb_api::search::SearchResult;
 
-use crate::services::steamgriddb_service::SteamgriddbService;
+use crate::{
+    config::Config,
+    error::ApiError,
+    metrics::Metrics,
+    services::{
+        cache::UpstreamCache,
+        circuit_breaker::{CircuitBreaker, CircuitStatus},
+        health::UpstreamProbe,
+        persistent_cache::PersistentCache,
+        rate_limiter::RateLimiter,
+        retry::RetryPolicy,
+        steamgriddb_service::SteamgriddbService,
+    },
+};
 
 pub struct GlobalState {
-    steamgriddb_api_client: Arc<steamgriddb_api::client::Client>,
-    reqwest_client: Arc<reqwest::Client>,
-    base_url: String,
+    steamgriddb_service: SteamgriddbService,
+    cache: Option<Arc<UpstreamCache>>,
+    upstream_probe: Arc<UpstreamProbe>,
+    metrics: Arc<Metrics>,
+    config: Config,
 }
 
 impl GlobalState {
-    pub fn new(auth_key: &str) -> Result<Self, Box<dyn Error>> {
+    pub fn new(auth_key: &str, config: &Config) -> Result<Self, ApiError> {
         let mut client_headers: HeaderMap<HeaderValue> = reqwest::header::HeaderMap::default();
         client_headers.insert(
             "Authorization",
             format!("Bearer {}", auth_key)
                 .parse()
-                .map_err(|e| format!("Failed to parse auth header: {}", e))?,
+                .map_err(|e| ApiError::Config(format!("Failed to parse auth header: {}", e)))?,
         );
 
         let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(5))
+            .timeout(Duration::from_millis(config.upstream.timeout_ms))
             .default_headers(client_headers)
-            .build()?;
+            .build()
+            .map_err(|e| ApiError::Config(format!("Failed to build http client: {}", e)))?;
 
-        let sgdb_client = steamgriddb_api::Client::new(auth_key);
-        let base_url = sgdb_client.base_url().to_string();
+        let cache = config
+            .cache
+            .enabled
+            .then(|| Arc::new(UpstreamCache::new(&config.cache)));
+
+        let breaker = config
+            .circuit_breaker
+            .enabled
+            .then(|| Arc::new(CircuitBreaker::from_config(&config.circuit_breaker)));
+        let limiter = config
+            .rate_limit
+            .enabled
+            .then(|| Arc::new(RateLimiter::from_config(&config.rate_limit)));
+
+        let metrics = Arc::new(Metrics::new());
+        metrics.register_components(cache.clone(), breaker.clone(), limiter.clone());
+
+        let mut steamgriddb_service =
+            SteamgriddbService::new(Arc::new(client), config.upstream.base_url.clone())
+                .with_retry_policy(RetryPolicy::from_config(&config.retry))
+                .with_metrics(metrics.clone());
+        if let Some(cache) = &cache {
+            steamgriddb_service = steamgriddb_service.with_cache(cache.clone());
+        }
+        if let Some(breaker) = breaker {
+            steamgriddb_service = steamgriddb_service.with_circuit_breaker(breaker);
+        }
+        if let Some(limiter) = limiter {
+            steamgriddb_service = steamgriddb_service.with_rate_limiter(limiter);
+        }
+        if config.persistent_cache.enabled {
+            let store = PersistentCache::open(&config.persistent_cache)?;
+            steamgriddb_service = steamgriddb_service.with_persistent_cache(Arc::new(store));
+        }
 
         Ok(Self {
-            steamgriddb_api_client: Arc::new(sgdb_client),
-            reqwest_client: Arc::new(client),
-            base_url,
+            steamgriddb_service,
+            cache,
+            upstream_probe: Arc::new(UpstreamProbe::new(Duration::from_secs(
+                config.health.probe_interval_secs,
+            ))),
+            metrics,
+            config: config.clone(),
         })
     }
 
-    pub async fn search_api(&self, query: &str) -> Result<Vec<SearchResult>, Box<dyn Error>> {
-        self.steamgriddb_api_client.search(query).await
+    pub async fn search_api(&self, query: &str) -> Result<Vec<SearchResult>, ApiError> {
+        self.steamgriddb_service.search(query).await
     }
 
+    /// Returns a handle to the shared service; clones share client, cache and in-flight requests.
     pub fn steamgriddb_service(&self) -> SteamgriddbService {
-        SteamgriddbService::new(
-            self.steamgriddb_api_client.clone(),
-            self.reqwest_client.clone(),
-            self.base_url.clone(),
-        )
+        self.steamgriddb_service.clone()
+    }
+
+    pub fn cache(&self) -> Option<&UpstreamCache> {
+        self.cache.as_deref()
+    }
+
+    /// Current state of the upstream circuit breaker, if enabled.
+    pub fn circuit_status(&self) -> Option<CircuitStatus> {
+        self.steamgriddb_service
+            .circuit_breaker()
+            .map(|breaker| breaker.status())
+    }
+
+    pub fn upstream_probe(&self) -> &Arc<UpstreamProbe> {
+        &self.upstream_probe
+    }
+
+    pub fn metrics(&self) -> &Arc<Metrics> {
+        &self.metrics
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn admin_token(&self) -> Option<&str> {
+        self.config.admin_token.as_deref()
     }
 }
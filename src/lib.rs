@@ -1,10 +1,20 @@
+pub mod config;
+mod error;
 mod global_state;
+pub mod metrics;
 mod model;
 pub mod services;
 
 pub mod routes;
+pub mod telemetry;
 
+pub use config::Config;
+pub use error::ApiError;
 pub use global_state::GlobalState;
 pub use model::steamgriddb_models;
-pub use model::{AssetList, Game, GameList, Response};
+pub use model::{
+    Asset, AssetAuthor, AssetList, EnrichmentStatus, ErrorCode, Game, GameDetails, GameList,
+    Response,
+};
+pub use services::endpoint::Endpoint;
 pub use services::steamgriddb_service::SteamgriddbService;
@@ -1,27 +1,63 @@
-use actix_web::{web, App, HttpServer};
+use actix_web::{middleware::from_fn, web, App, HttpServer};
 use cosy_gameapi::{
-    routes::{get_assets_by_id, search_games},
-    GlobalState,
+    metrics::track_requests,
+    routes::{
+        self, get_assets_by_id, get_assets_by_platform_id, get_game_by_id, get_game_by_platform_id,
+        liveness, prometheus_metrics, readiness, refresh_game, search_games, upstream_status,
+    },
+    telemetry::{self, trace_requests},
+    Config, GlobalState,
 };
 
 #[actix_web::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let Ok(auth_key) = std::env::var("COSY_GAMEAPI_SGDB_API_KEY") else {
+    let config = Config::load().map_err(|e| e.to_string())?;
+    telemetry::init(&config.log).map_err(|e| e.to_string())?;
+    let Some(auth_key) = config.upstream.api_key.clone() else {
         return Err("COSY_GAMEAPI_SGDB_API_KEY environment variable not set".into());
     };
 
-    let global_state = web::Data::new(GlobalState::new(&auth_key)?);
+    let global_state =
+        web::Data::new(GlobalState::new(&auth_key, &config).map_err(|e| e.to_string())?);
 
-    HttpServer::new(move || {
+    let mut server = HttpServer::new(move || {
         App::new()
+            .wrap(from_fn(track_requests))
+            .wrap(from_fn(trace_requests))
             .service(get_assets_by_id)
+            .service(get_assets_by_platform_id)
             .service(search_games)
+            .service(get_game_by_id)
+            .service(get_game_by_platform_id)
+            .service(refresh_game)
+            .service(upstream_status)
+            .service(liveness)
+            .service(readiness)
+            .service(prometheus_metrics)
             .app_data(global_state.clone())
-    })
-    .bind(("0.0.0.0", 8080))?
-    .run()
-    .await
-    .map_err(|e| format!("failed to run server: {}", e))?;
+            .app_data(routes::query_config())
+            .app_data(routes::path_config())
+    });
+    if let Some(workers) = config.server.workers {
+        server = server.workers(workers);
+    }
+
+    tracing::info!(
+        host = %config.server.host,
+        port = config.server.port,
+        "starting server"
+    );
+    server
+        .bind((config.server.host.as_str(), config.server.port))
+        .map_err(|e| {
+            format!(
+                "failed to bind {}:{}: {}",
+                config.server.host, config.server.port, e
+            )
+        })?
+        .run()
+        .await
+        .map_err(|e| format!("failed to run server: {}", e))?;
 
     Ok(())
 }
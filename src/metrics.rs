@@ -0,0 +1,426 @@
+use std::{borrow::Cow, sync::Arc, time::Instant};
+
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    middleware::Next,
+    web::Data,
+};
+use prometheus::{
+    core::{Collector, Desc},
+    proto::MetricFamily,
+    Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+
+use crate::{
+    error::ApiError,
+    services::{
+        asset_filter::AssetKind,
+        cache::UpstreamCache,
+        circuit_breaker::{CircuitBreaker, CircuitState},
+        endpoint::Endpoint,
+        rate_limiter::RateLimiter,
+    },
+    GlobalState,
+};
+
+const NAMESPACE: &str = "cosy_gameapi";
+
+/// Which asset a search result was enriched with.
+#[derive(Debug, Clone, Copy)]
+pub enum EnrichmentKind {
+    Logo,
+    Hero,
+    Grid,
+    Icon,
+}
+
+impl From<AssetKind> for EnrichmentKind {
+    fn from(kind: AssetKind) -> Self {
+        match kind {
+            AssetKind::Logo => EnrichmentKind::Logo,
+            AssetKind::Hero => EnrichmentKind::Hero,
+            AssetKind::Grid => EnrichmentKind::Grid,
+            AssetKind::Icon => EnrichmentKind::Icon,
+        }
+    }
+}
+
+impl EnrichmentKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            EnrichmentKind::Logo => "logo",
+            EnrichmentKind::Hero => "hero",
+            EnrichmentKind::Grid => "grid",
+            EnrichmentKind::Icon => "icon",
+        }
+    }
+}
+
+/// Prometheus metrics of one service instance, kept in their own registry.
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    upstream_requests: IntCounterVec,
+    upstream_duration: HistogramVec,
+    enrichments: IntCounterVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let http_requests = IntCounterVec::new(
+            Opts::new(
+                "http_requests_total",
+                "HTTP requests handled, by route and status",
+            )
+            .namespace(NAMESPACE),
+            &["method", "route", "status"],
+        )
+        .expect("valid metric");
+        let http_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time spent handling HTTP requests, by route",
+            )
+            .namespace(NAMESPACE),
+            &["method", "route"],
+        )
+        .expect("valid metric");
+        let upstream_requests = IntCounterVec::new(
+            Opts::new(
+                "upstream_requests_total",
+                "Requests to SteamGridDB, by endpoint and outcome",
+            )
+            .namespace(NAMESPACE),
+            &["endpoint", "status"],
+        )
+        .expect("valid metric");
+        let upstream_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "upstream_request_duration_seconds",
+                "Duration of requests sent to SteamGridDB, by endpoint",
+            )
+            .namespace(NAMESPACE),
+            &["endpoint"],
+        )
+        .expect("valid metric");
+        let enrichments = IntCounterVec::new(
+            Opts::new(
+                "enrichments_total",
+                "Logo, hero, grid and icon lookups for search results, by outcome",
+            )
+            .namespace(NAMESPACE),
+            &["kind", "outcome"],
+        )
+        .expect("valid metric");
+
+        for collector in [
+            Box::new(http_requests.clone()) as Box<dyn Collector>,
+            Box::new(http_duration.clone()),
+            Box::new(upstream_requests.clone()),
+            Box::new(upstream_duration.clone()),
+            Box::new(enrichments.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metric registered once");
+        }
+
+        Self {
+            registry,
+            http_requests,
+            http_duration,
+            upstream_requests,
+            upstream_duration,
+            enrichments,
+        }
+    }
+
+    /// Exposes the state of the caches, circuit breaker and rate limiter, read on every scrape.
+    pub fn register_components(
+        &self,
+        cache: Option<Arc<UpstreamCache>>,
+        breaker: Option<Arc<CircuitBreaker>>,
+        limiter: Option<Arc<RateLimiter>>,
+    ) {
+        self.registry
+            .register(Box::new(ComponentCollector::new(cache, breaker, limiter)))
+            .expect("metric registered once");
+    }
+
+    pub fn observe_http(&self, method: &str, route: &str, status: u16, seconds: f64) {
+        self.http_requests
+            .with_label_values(&[method, route, &status.to_string()])
+            .inc();
+        self.http_duration
+            .with_label_values(&[method, route])
+            .observe(seconds);
+    }
+
+    /// Records one upstream attempt. `seconds` is `None` if no request was sent.
+    pub fn observe_upstream<T>(
+        &self,
+        endpoint: Endpoint,
+        result: &Result<T, ApiError>,
+        seconds: Option<f64>,
+    ) {
+        self.upstream_requests
+            .with_label_values(&[endpoint.as_str(), &upstream_status(result)])
+            .inc();
+        if let Some(seconds) = seconds {
+            self.upstream_duration
+                .with_label_values(&[endpoint.as_str()])
+                .observe(seconds);
+        }
+    }
+
+    /// Records whether enriching a search result found an asset (`Ok(true)`), found none
+    /// (`Ok(false)`) or failed.
+    pub fn observe_enrichment(&self, kind: EnrichmentKind, outcome: Result<bool, ()>) {
+        let outcome = match outcome {
+            Ok(true) => "found",
+            Ok(false) => "missing",
+            Err(()) => "failed",
+        };
+        self.enrichments
+            .with_label_values(&[kind.as_str(), outcome])
+            .inc();
+    }
+
+    /// All metrics in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding never fails");
+        String::from_utf8(buffer).expect("text encoding is utf-8")
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The `status` label of an upstream attempt: the HTTP status, or what kept it from having one.
+pub(crate) fn upstream_status<T>(result: &Result<T, ApiError>) -> Cow<'static, str> {
+    match result {
+        Ok(_) => "200".into(),
+        Err(ApiError::UpstreamStatus { status, .. }) => status.to_string().into(),
+        Err(ApiError::UpstreamRateLimited { .. }) => "429".into(),
+        Err(ApiError::Timeout { .. }) => "timeout".into(),
+        Err(ApiError::Request { .. }) => "error".into(),
+        Err(ApiError::Decode { .. }) => "invalid_body".into(),
+        Err(ApiError::CircuitOpen { .. }) => "circuit_open".into(),
+        Err(ApiError::Throttled { .. }) => "throttled".into(),
+        Err(ApiError::Unsuccessful { .. } | ApiError::Config(_)) => "error".into(),
+    }
+}
+
+/// Middleware recording count and duration of every request, labelled with its route
+/// pattern (e.g. `/assets/{game_id}`) rather than the concrete path.
+pub async fn track_requests(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let started = Instant::now();
+    let metrics = req
+        .app_data::<Data<GlobalState>>()
+        .map(|state| state.metrics().clone());
+    let method = req.method().to_string();
+
+    let res = next.call(req).await?;
+
+    if let Some(metrics) = metrics {
+        let route = res
+            .request()
+            .match_pattern()
+            .unwrap_or_else(|| "unmatched".into());
+        metrics.observe_http(
+            &method,
+            &route,
+            res.status().as_u16(),
+            started.elapsed().as_secs_f64(),
+        );
+    }
+    Ok(res)
+}
+
+/// Reads component state at scrape time instead of mirroring it into registered metrics.
+struct ComponentCollector {
+    cache: Option<Arc<UpstreamCache>>,
+    breaker: Option<Arc<CircuitBreaker>>,
+    limiter: Option<Arc<RateLimiter>>,
+    descs: Vec<Desc>,
+}
+
+impl ComponentCollector {
+    fn new(
+        cache: Option<Arc<UpstreamCache>>,
+        breaker: Option<Arc<CircuitBreaker>>,
+        limiter: Option<Arc<RateLimiter>>,
+    ) -> Self {
+        let templates = ComponentMetrics::new();
+        let descs = templates
+            .collectors()
+            .iter()
+            .flat_map(|collector| collector.desc().into_iter().cloned())
+            .collect();
+
+        Self {
+            cache,
+            breaker,
+            limiter,
+            descs,
+        }
+    }
+}
+
+impl Collector for ComponentCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.descs.iter().collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let metrics = ComponentMetrics::new();
+
+        if let Some(cache) = &self.cache {
+            for (name, stats) in cache.stats() {
+                metrics
+                    .cache_hits
+                    .with_label_values(&[name])
+                    .inc_by(stats.hits);
+                metrics
+                    .cache_misses
+                    .with_label_values(&[name])
+                    .inc_by(stats.misses);
+                metrics
+                    .cache_entries
+                    .with_label_values(&[name])
+                    .set(stats.entries as i64);
+                let lookups = stats.hits + stats.misses;
+                if lookups > 0 {
+                    metrics
+                        .cache_hit_ratio
+                        .with_label_values(&[name])
+                        .set(stats.hits as f64 / lookups as f64);
+                }
+            }
+        }
+
+        if let Some(breaker) = &self.breaker {
+            let status = breaker.status();
+            for state in [
+                CircuitState::Closed,
+                CircuitState::Open,
+                CircuitState::HalfOpen,
+            ] {
+                metrics
+                    .circuit_state
+                    .with_label_values(&[state.as_str()])
+                    .set(i64::from(status.state == state));
+            }
+            metrics
+                .circuit_opened
+                .with_label_values(&[])
+                .inc_by(status.times_opened);
+        }
+
+        if let Some(limiter) = &self.limiter {
+            metrics.rate_limit_queued.set(limiter.queued() as i64);
+        }
+
+        metrics
+            .collectors()
+            .iter()
+            .flat_map(|collector| collector.collect())
+            .collect()
+    }
+}
+
+/// Unregistered metrics filled from component state on every scrape.
+struct ComponentMetrics {
+    cache_hits: IntCounterVec,
+    cache_misses: IntCounterVec,
+    cache_entries: IntGaugeVec,
+    cache_hit_ratio: GaugeVec,
+    circuit_state: IntGaugeVec,
+    circuit_opened: IntCounterVec,
+    rate_limit_queued: IntGauge,
+}
+
+impl ComponentMetrics {
+    fn new() -> Self {
+        let opts = |name: &str, help: &str| Opts::new(name, help).namespace(NAMESPACE);
+
+        Self {
+            cache_hits: IntCounterVec::new(
+                opts(
+                    "cache_hits_total",
+                    "In-memory cache lookups answered from the cache",
+                ),
+                &["cache"],
+            )
+            .expect("valid metric"),
+            cache_misses: IntCounterVec::new(
+                opts("cache_misses_total", "In-memory cache lookups that missed"),
+                &["cache"],
+            )
+            .expect("valid metric"),
+            cache_entries: IntGaugeVec::new(
+                opts(
+                    "cache_entries",
+                    "Entries currently held by the in-memory cache",
+                ),
+                &["cache"],
+            )
+            .expect("valid metric"),
+            cache_hit_ratio: GaugeVec::new(
+                opts(
+                    "cache_hit_ratio",
+                    "Share of in-memory cache lookups answered from the cache since startup",
+                ),
+                &["cache"],
+            )
+            .expect("valid metric"),
+            circuit_state: IntGaugeVec::new(
+                opts(
+                    "circuit_breaker_state",
+                    "1 for the current state of the upstream circuit breaker, 0 otherwise",
+                ),
+                &["state"],
+            )
+            .expect("valid metric"),
+            circuit_opened: IntCounterVec::new(
+                opts(
+                    "circuit_breaker_opened_total",
+                    "Times the upstream circuit breaker has opened",
+                ),
+                &[],
+            )
+            .expect("valid metric"),
+            rate_limit_queued: IntGauge::with_opts(opts(
+                "rate_limit_queued",
+                "Upstream requests waiting for the rate limiter",
+            ))
+            .expect("valid metric"),
+        }
+    }
+
+    fn collectors(&self) -> [&dyn Collector; 7] {
+        [
+            &self.cache_hits,
+            &self.cache_misses,
+            &self.cache_entries,
+            &self.cache_hit_ratio,
+            &self.circuit_state,
+            &self.circuit_opened,
+            &self.rate_limit_queued,
+        ]
+    }
+}
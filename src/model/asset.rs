@@ -1,24 +1,82 @@
 use serde::Serialize;
 
+use crate::steamgriddb_models;
+
 #[derive(Serialize, Clone)]
 pub struct Asset {
+    /// SteamGridDB id of the asset.
+    pub id: u32,
     pub width: u32,
     pub height: u32,
     pub url: String,
+    /// Scaled down preview of `url`.
+    pub thumb: String,
+    pub score: u32,
+    pub upvotes: u32,
+    pub downvotes: u32,
+    pub style: String,
+    pub mime: String,
+    pub language: String,
+    pub nsfw: bool,
+    pub humor: bool,
+    pub epilepsy: bool,
+    pub author: AssetAuthor,
+}
+
+/// The SteamGridDB user who uploaded an asset.
+#[derive(Serialize, Clone)]
+pub struct AssetAuthor {
+    pub name: String,
+    pub steam64: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
 }
 
-impl From<steamgriddb_api::images::Image> for Asset {
-    fn from(image: steamgriddb_api::images::Image) -> Self {
-        Asset {
-            width: image.width,
-            height: image.height,
-            url: image.url,
+impl From<steamgriddb_models::ResponseAuthor> for AssetAuthor {
+    fn from(author: steamgriddb_models::ResponseAuthor) -> Self {
+        AssetAuthor {
+            name: author.name,
+            steam64: author.steam64,
+            avatar: author.avatar,
         }
     }
 }
 
+macro_rules! asset_from_listing {
+    ($data:ty) => {
+        impl From<$data> for Asset {
+            fn from(data: $data) -> Self {
+                Asset {
+                    id: data.id,
+                    width: data.width,
+                    height: data.height,
+                    url: data.url,
+                    thumb: data.thumb,
+                    score: data.score,
+                    upvotes: data.upvotes,
+                    downvotes: data.downvotes,
+                    style: data.style,
+                    mime: data.mime,
+                    language: data.language,
+                    nsfw: data.nsfw,
+                    humor: data.humor,
+                    epilepsy: data.epilepsy,
+                    author: data.author.into(),
+                }
+            }
+        }
+    };
+}
+
+asset_from_listing!(steamgriddb_models::GridResponseData);
+asset_from_listing!(steamgriddb_models::HeroResponseData);
+asset_from_listing!(steamgriddb_models::LogoResposeData);
+asset_from_listing!(steamgriddb_models::IconResponseData);
+
 #[derive(Serialize, Clone)]
 pub struct AssetList {
     pub assets: Vec<Asset>,
     pub is_final: bool,
+    /// Number of assets matching the request on SteamGridDB, across all pages.
+    pub total: u32,
 }
@@ -1,30 +1,195 @@
+use chrono::Datelike;
 use serde::Serialize;
 
+use super::Asset;
+use crate::steamgriddb_models;
+
 #[derive(Serialize, Clone)]
 pub struct Game {
     pub id: usize,
     pub name: String,
 
+    /// Unix timestamp.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_date: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_year: Option<i32>,
+
+    pub verified: bool,
+
+    /// Platforms the game is known on, e.g. `steam`; only filled in when asked for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub types: Option<Vec<String>>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub logo_url: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hero_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid_url: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+
+    /// Outcome of each asset lookup that was asked for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo_status: Option<EnrichmentStatus>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hero_status: Option<EnrichmentStatus>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid_status: Option<EnrichmentStatus>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_status: Option<EnrichmentStatus>,
+}
+
+impl Game {
+    /// Whether any asset lookup for this game failed.
+    pub fn enrichment_failed(&self) -> bool {
+        [
+            self.logo_status,
+            self.hero_status,
+            self.grid_status,
+            self.icon_status,
+        ]
+        .contains(&Some(EnrichmentStatus::Error))
+    }
+}
+
+/// Outcome of looking up an asset for a search result or game details.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EnrichmentStatus {
+    /// The asset's url is set.
+    Found,
+    /// SteamGridDB has no such asset for the game.
+    None,
+    /// The lookup failed; asking again later may succeed.
+    Error,
 }
 
 impl From<steamgriddb_api::search::SearchResult> for Game {
     fn from(result: steamgriddb_api::search::SearchResult) -> Self {
+        let release_date = result.release_date.map(|date| date as i64);
         Game {
             id: result.id,
             name: result.name,
+            release_date,
+            release_year: release_date.and_then(release_year),
+            verified: result.verified,
+            types: None,
+            logo_url: None,
+            hero_url: None,
+            grid_url: None,
+            icon_url: None,
+            logo_status: None,
+            hero_status: None,
+            grid_status: None,
+            icon_status: None,
+        }
+    }
+}
+
+impl From<steamgriddb_models::GameData> for Game {
+    fn from(data: steamgriddb_models::GameData) -> Self {
+        Game {
+            id: data.id,
+            name: data.name,
+            release_date: data.release_date,
+            release_year: data.release_date.and_then(release_year),
+            verified: data.verified,
+            types: None,
             logo_url: None,
             hero_url: None,
+            grid_url: None,
+            icon_url: None,
+            logo_status: None,
+            hero_status: None,
+            grid_status: None,
+            icon_status: None,
         }
     }
 }
 
+/// Everything SteamGridDB knows about a game, with optionally embedded assets.
+#[derive(Serialize, Clone)]
+pub struct GameDetails {
+    pub id: usize,
+    pub name: String,
+    /// Unix timestamp.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_date: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_year: Option<i32>,
+    /// Platforms the game is known on, e.g. `steam`.
+    pub types: Vec<String>,
+    pub verified: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid: Option<Asset>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hero: Option<Asset>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo: Option<Asset>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<Asset>,
+
+    /// Outcome of each embedded asset lookup that was asked for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid_status: Option<EnrichmentStatus>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hero_status: Option<EnrichmentStatus>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logo_status: Option<EnrichmentStatus>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_status: Option<EnrichmentStatus>,
+
+    /// Set when a lookup of an embedded asset failed; see the `*_status` fields.
+    pub partial: bool,
+}
+
+impl From<steamgriddb_models::GameData> for GameDetails {
+    fn from(data: steamgriddb_models::GameData) -> Self {
+        GameDetails {
+            id: data.id,
+            name: data.name,
+            release_date: data.release_date,
+            release_year: data.release_date.and_then(release_year),
+            types: data.types,
+            verified: data.verified,
+            grid: None,
+            hero: None,
+            logo: None,
+            icon: None,
+            grid_status: None,
+            hero_status: None,
+            logo_status: None,
+            icon_status: None,
+            partial: false,
+        }
+    }
+}
+
+/// The (UTC) year of a release date given as Unix timestamp.
+fn release_year(timestamp: i64) -> Option<i32> {
+    chrono::DateTime::from_timestamp(timestamp, 0).map(|date| date.year())
+}
+
 #[derive(Serialize, Clone)]
 pub struct GameList {
     pub games: Vec<Game>,
     pub is_final: bool,
+    /// Set when an asset lookup of any game failed; see the games' `*_status`.
+    pub partial: bool,
 }
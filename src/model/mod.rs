@@ -3,6 +3,6 @@ mod game;
 mod response;
 pub mod steamgriddb_models;
 
-pub use asset::AssetList;
-pub use game::{Game, GameList};
-pub use response::Response;
+pub use asset::{Asset, AssetAuthor, AssetList};
+pub use game::{EnrichmentStatus, Game, GameDetails, GameList};
+pub use response::{ErrorCode, Response};
@@ -1,9 +1,27 @@
 use actix_web::{
     http::{self, StatusCode},
-    Responder,
+    HttpMessage, Responder,
 };
 use serde::Serialize;
 
+use crate::{error::ApiError, telemetry::RequestId};
+
+/// Stable, machine-readable error identifiers returned alongside error messages.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InvalidQuery,
+    Unauthorized,
+    GameNotFound,
+    UpstreamTimeout,
+    UpstreamRateLimited,
+    UpstreamUnavailable,
+    UpstreamError,
+    Throttled,
+    NotReady,
+    InternalError,
+}
+
 #[derive(Serialize, Clone, Default)]
 pub struct Response<T: Serialize> {
     success: bool,
@@ -15,6 +33,13 @@ pub struct Response<T: Serialize> {
     #[serde(skip_serializing_if = "Option::is_none")]
     message: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_code: Option<ErrorCode>,
+
+    /// Set on error responses, so a failure reported by a client can be found in the logs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+
     #[serde(skip_serializing)]
     code: Option<http::StatusCode>,
 }
@@ -25,6 +50,8 @@ impl<T: Serialize> Response<T> {
             success: true,
             data: Some(data),
             message: None,
+            error_code: None,
+            request_id: None,
             code: None,
             timestamp: 0,
         }
@@ -35,17 +62,80 @@ impl<T: Serialize> Response<T> {
             success: false,
             data: None,
             message: Some(message),
+            error_code: None,
+            request_id: None,
             code: Some(code),
             timestamp: 0,
         }
     }
+
+    pub fn error_with_code(message: String, code: http::StatusCode, error_code: ErrorCode) -> Self {
+        Self {
+            error_code: Some(error_code),
+            ..Self::error(message, code)
+        }
+    }
+}
+
+impl<T: Serialize> Response<T> {
+    /// Attaches `data` to an error response, e.g. to explain which component failed.
+    pub fn with_data(mut self, data: T) -> Self {
+        self.data = Some(data);
+        self
+    }
+}
+
+impl<T: Serialize> From<ApiError> for Response<T> {
+    fn from(err: ApiError) -> Self {
+        tracing::warn!(error = %err, "request failed");
+
+        let (code, error_code) = match &err {
+            ApiError::UpstreamStatus {
+                game_id: Some(game_id),
+                status: 404,
+                ..
+            } => {
+                return Self::error_with_code(
+                    format!("Game {} not found", game_id),
+                    StatusCode::NOT_FOUND,
+                    ErrorCode::GameNotFound,
+                );
+            }
+            ApiError::UpstreamStatus { status: 429, .. } | ApiError::UpstreamRateLimited { .. } => {
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    ErrorCode::UpstreamRateLimited,
+                )
+            }
+            ApiError::UpstreamStatus { status: 503, .. }
+            | ApiError::Request { .. }
+            | ApiError::CircuitOpen { .. } => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                ErrorCode::UpstreamUnavailable,
+            ),
+            ApiError::Throttled { .. } => (StatusCode::SERVICE_UNAVAILABLE, ErrorCode::Throttled),
+            ApiError::Timeout { .. } => (StatusCode::GATEWAY_TIMEOUT, ErrorCode::UpstreamTimeout),
+            ApiError::UpstreamStatus { .. }
+            | ApiError::Decode { .. }
+            | ApiError::Unsuccessful { .. } => (StatusCode::BAD_GATEWAY, ErrorCode::UpstreamError),
+            ApiError::Config(_) => (StatusCode::INTERNAL_SERVER_ERROR, ErrorCode::InternalError),
+        };
+
+        Self::error_with_code(err.to_string(), code, error_code)
+    }
 }
 
 impl<T: Serialize> Responder for Response<T> {
     type Body = actix_web::body::BoxBody;
 
-    fn respond_to(mut self, _req: &actix_web::HttpRequest) -> actix_web::HttpResponse<Self::Body> {
+    fn respond_to(mut self, req: &actix_web::HttpRequest) -> actix_web::HttpResponse<Self::Body> {
         self.success = self.code.unwrap_or(StatusCode::OK).is_success();
+        if !self.success {
+            self.request_id = req
+                .extensions()
+                .get::<RequestId>()
+                .map(|id| id.as_str().to_string());
+        }
         self.timestamp = chrono::Utc::now().timestamp_millis() as u64;
         actix_web::HttpResponse::build(self.code.unwrap_or(StatusCode::OK)).json(self)
     }
@@ -1,6 +1,78 @@
 #![allow(dead_code)] // we have to keep unused fields for deserialization, so don't warn about them
 
-use serde::Deserialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// A page of assets as listed by one of SteamGridDB's `/{kind}/game/{id}` routes.
+pub trait AssetPage: DeserializeOwned {
+    type Item;
+
+    fn success(&self) -> bool;
+    fn into_listing(self) -> Listing<Self::Item>;
+}
+
+/// Some of the assets of a game, along with how many match the request in total.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Listing<T> {
+    pub data: Vec<T>,
+    pub total: u32,
+}
+
+/// Answer of SteamGridDB's `/games/...` lookups.
+#[derive(Deserialize)]
+pub struct GameResponse {
+    pub success: bool,
+    pub data: Option<GameData>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct GameData {
+    pub id: usize,
+    pub name: String,
+    /// Platforms the game is known on, e.g. `steam`.
+    #[serde(default)]
+    pub types: Vec<String>,
+    /// Unix timestamp.
+    pub release_date: Option<i64>,
+    #[serde(default)]
+    pub verified: bool,
+}
+
+/// Unlike the other listings, a failed grid listing comes without `data` or paging fields.
+/// A missing `total` is read as 0.
+#[derive(Deserialize)]
+pub struct GridsResponse {
+    pub success: bool,
+    #[serde(default)]
+    page: u32,
+    #[serde(default)]
+    total: u32,
+    #[serde(default)]
+    limit: u32,
+    #[serde(default)]
+    pub data: Vec<GridResponseData>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct GridResponseData {
+    pub id: u32,
+    pub score: u32,
+    pub style: String,
+    pub width: u32,
+    pub height: u32,
+    pub nsfw: bool,
+    pub humor: bool,
+    pub notes: Option<String>,
+    pub mime: String,
+    pub language: String,
+    pub url: String,
+    pub thumb: String,
+    pub lock: bool,
+    pub epilepsy: bool,
+    pub upvotes: u32,
+    pub downvotes: u32,
+    pub author: ResponseAuthor,
+    pub tags: Option<Vec<String>>,
+}
 
 #[derive(Deserialize)]
 pub struct HeroesResponse {
@@ -11,26 +83,26 @@ pub struct HeroesResponse {
     pub data: Vec<HeroResponseData>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct HeroResponseData {
-    id: u32,
-    score: u32,
-    style: String,
-    width: u32,
-    height: u32,
-    nsfw: bool,
-    humor: bool,
-    notes: Option<String>,
-    mime: String,
-    language: String,
+    pub id: u32,
+    pub score: u32,
+    pub style: String,
+    pub width: u32,
+    pub height: u32,
+    pub nsfw: bool,
+    pub humor: bool,
+    pub notes: Option<String>,
+    pub mime: String,
+    pub language: String,
     pub url: String,
     pub thumb: String,
-    lock: bool,
-    epilepsy: bool,
-    upvotes: u32,
-    downvotes: u32,
-    author: ResponseAuthor,
-    tags: Option<Vec<String>>,
+    pub lock: bool,
+    pub epilepsy: bool,
+    pub upvotes: u32,
+    pub downvotes: u32,
+    pub author: ResponseAuthor,
+    pub tags: Option<Vec<String>>,
 }
 
 #[derive(Deserialize)]
@@ -42,31 +114,86 @@ pub struct LogosResponse {
     pub data: Vec<LogoResposeData>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize, Clone)]
 pub struct LogoResposeData {
-    id: u32,
-    score: u32,
-    style: String,
-    width: u32,
-    height: u32,
-    nsfw: bool,
-    humor: bool,
-    notes: Option<String>,
-    mime: String,
-    language: String,
+    pub id: u32,
+    pub score: u32,
+    pub style: String,
+    pub width: u32,
+    pub height: u32,
+    pub nsfw: bool,
+    pub humor: bool,
+    pub notes: Option<String>,
+    pub mime: String,
+    pub language: String,
     pub url: String,
     pub thumb: String,
-    lock: bool,
-    epilepsy: bool,
-    upvotes: u32,
-    downvotes: u32,
-    author: ResponseAuthor,
-    tags: Option<Vec<String>>,
+    pub lock: bool,
+    pub epilepsy: bool,
+    pub upvotes: u32,
+    pub downvotes: u32,
+    pub author: ResponseAuthor,
+    pub tags: Option<Vec<String>>,
 }
 
 #[derive(Deserialize)]
-struct ResponseAuthor {
-    name: String,
-    steam64: String,
-    avatar: String,
+pub struct IconsResponse {
+    pub success: bool,
+    page: u32,
+    total: u32,
+    limit: u32,
+    pub data: Vec<IconResponseData>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct IconResponseData {
+    pub id: u32,
+    pub score: u32,
+    pub style: String,
+    pub width: u32,
+    pub height: u32,
+    pub nsfw: bool,
+    pub humor: bool,
+    pub notes: Option<String>,
+    pub mime: String,
+    pub language: String,
+    pub url: String,
+    pub thumb: String,
+    pub lock: bool,
+    pub epilepsy: bool,
+    pub upvotes: u32,
+    pub downvotes: u32,
+    pub author: ResponseAuthor,
+    pub tags: Option<Vec<String>>,
+}
+
+macro_rules! asset_page {
+    ($response:ty, $item:ty) => {
+        impl AssetPage for $response {
+            type Item = $item;
+
+            fn success(&self) -> bool {
+                self.success
+            }
+
+            fn into_listing(self) -> Listing<$item> {
+                Listing {
+                    data: self.data,
+                    total: self.total,
+                }
+            }
+        }
+    };
+}
+
+asset_page!(GridsResponse, GridResponseData);
+asset_page!(HeroesResponse, HeroResponseData);
+asset_page!(LogosResponse, LogoResposeData);
+asset_page!(IconsResponse, IconResponseData);
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct ResponseAuthor {
+    pub name: String,
+    pub steam64: String,
+    pub avatar: Option<String>,
 }
@@ -0,0 +1,60 @@
+use actix_web::{
+    http::{header, StatusCode},
+    post,
+    web::{self, Data},
+    HttpRequest,
+};
+use serde::Serialize;
+use subtle::ConstantTimeEq;
+
+use crate::{
+    model::{ErrorCode, Response},
+    GlobalState,
+};
+
+#[derive(Serialize)]
+pub struct GameRefresh {
+    pub game_id: usize,
+}
+
+/// Drops all cached data for a game, including remembered missing logos, heroes and icons.
+#[post("/admin/games/{game_id}/refresh")]
+pub async fn refresh_game(
+    global_data: Data<GlobalState>,
+    path: web::Path<usize>,
+    req: HttpRequest,
+) -> Response<GameRefresh> {
+    if let Err(message) = authorize(&global_data, &req) {
+        return Response::error_with_code(
+            message.into(),
+            StatusCode::UNAUTHORIZED,
+            ErrorCode::Unauthorized,
+        );
+    }
+
+    let game_id = path.into_inner();
+    global_data
+        .steamgriddb_service()
+        .invalidate_game(game_id)
+        .await;
+
+    Response::success(GameRefresh { game_id })
+}
+
+fn authorize(global_data: &GlobalState, req: &HttpRequest) -> Result<(), &'static str> {
+    let Some(token) = global_data.admin_token() else {
+        return Err("admin routes are disabled");
+    };
+
+    let provided = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    // compared in constant time, so response times do not reveal how much of a guess is right
+    match provided {
+        Some(provided) if bool::from(provided.as_bytes().ct_eq(token.as_bytes())) => Ok(()),
+        _ => Err("missing or invalid admin token"),
+    }
+}
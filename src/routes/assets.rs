@@ -5,8 +5,14 @@ use actix_web::{
 };
 
 use crate::{
-    model::{AssetList, Response},
-    GlobalState,
+    model::{Asset, AssetList, ErrorCode, Response},
+    routes::games::lookup_platform_game,
+    services::{
+        asset_filter::{parse_list, AssetFilter, AssetKind, Inclusion},
+        platform::Platform,
+    },
+    steamgriddb_models::Listing,
+    ApiError, GlobalState,
 };
 use serde::Deserialize;
 
@@ -14,6 +20,40 @@ use serde::Deserialize;
 pub struct FetchAssetsQuery {
     limit: Option<u32>,
     offset: Option<u32>,
+    #[serde(rename = "type", default)]
+    kind: AssetKind,
+    /// Comma separated, e.g. `alternate,blurred`.
+    style: Option<String>,
+    /// Comma separated, e.g. `600x900,460x215`.
+    dimensions: Option<String>,
+    /// Comma separated, e.g. `png,webp`.
+    mime: Option<String>,
+    animated: Option<bool>,
+    nsfw: Option<String>,
+    humor: Option<String>,
+    epilepsy: Option<String>,
+}
+
+impl FetchAssetsQuery {
+    /// The SteamGridDB filters asked for, or a message naming the values allowed for the
+    /// asset type of the first invalid one. The content policy is not applied yet.
+    fn filter(&self) -> Result<AssetFilter, String> {
+        let list = |name, value: &Option<String>, allowed| {
+            value
+                .as_deref()
+                .map_or(Ok(Vec::new()), |value| parse_list(name, value, allowed))
+        };
+
+        Ok(AssetFilter {
+            styles: list("style", &self.style, self.kind.styles())?,
+            dimensions: list("dimensions", &self.dimensions, self.kind.dimensions())?,
+            mimes: list("mime", &self.mime, self.kind.mimes())?,
+            animated: self.animated,
+            nsfw: Inclusion::parse_opt("nsfw", self.nsfw.as_deref())?,
+            humor: Inclusion::parse_opt("humor", self.humor.as_deref())?,
+            epilepsy: Inclusion::parse_opt("epilepsy", self.epilepsy.as_deref())?,
+        })
+    }
 }
 
 #[get("/assets/{game_id}")]
@@ -22,25 +62,91 @@ pub async fn get_assets_by_id(
     path: web::Path<usize>,
     query: Query<FetchAssetsQuery>,
 ) -> Response<AssetList> {
-    let game_id = path.into_inner();
-    let service = global_data.steamgriddb_service();
-    let Ok(results) = service.fetch_assets_by_game_id(game_id).await else {
-        return Response::error(
-            "Failed to fetch assets".into(),
-            StatusCode::INTERNAL_SERVER_ERROR,
-        );
+    list_assets(&global_data, path.into_inner(), &query).await
+}
+
+/// Like [`get_assets_by_id`], for a game given by its id on another store.
+#[get("/assets/platform/{platform}/{id}")]
+pub async fn get_assets_by_platform_id(
+    global_data: Data<GlobalState>,
+    path: web::Path<(Platform, String)>,
+    query: Query<FetchAssetsQuery>,
+) -> Response<AssetList> {
+    let (platform, id) = path.into_inner();
+    match lookup_platform_game(&global_data, platform, &id).await {
+        Ok(game) => list_assets(&global_data, game.id, &query).await,
+        Err(response) => response,
+    }
+}
+
+async fn list_assets(
+    global_data: &GlobalState,
+    game_id: usize,
+    query: &FetchAssetsQuery,
+) -> Response<AssetList> {
+    let policy = &global_data.config().content;
+    let filter = match query.filter().and_then(|filter| filter.with_policy(policy)) {
+        Ok(filter) => filter,
+        Err(message) => {
+            return Response::error_with_code(
+                message,
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidQuery,
+            )
+        }
+    };
+
+    let offset = query.offset.unwrap_or(0);
+    let limit = global_data.config().pagination.limit(query.limit);
+
+    let listing = match fetch_assets(global_data, query.kind, game_id, &filter, offset, limit).await
+    {
+        Ok(listing) => listing,
+        Err(err) => return err.into(),
     };
 
     let is_final =
-        results.len() <= query.limit.unwrap_or(15) as usize + query.offset.unwrap_or(0) as usize;
+        listing.data.len() < limit as usize || offset.saturating_add(limit) >= listing.total;
 
     Response::success(AssetList {
-        assets: results
-            .into_iter()
-            .skip(query.offset.unwrap_or(0) as usize)
-            .take(query.limit.unwrap_or(15) as usize)
-            .map(|img| img.into())
-            .collect(),
+        assets: listing.data,
         is_final,
+        total: listing.total,
     })
 }
+
+pub(super) async fn fetch_assets(
+    global_data: &GlobalState,
+    kind: AssetKind,
+    game_id: usize,
+    filter: &AssetFilter,
+    offset: u32,
+    limit: u32,
+) -> Result<Listing<Asset>, ApiError> {
+    fn into_assets<T: Into<Asset>>(listing: Listing<T>) -> Listing<Asset> {
+        Listing {
+            data: listing.data.into_iter().map(Into::into).collect(),
+            total: listing.total,
+        }
+    }
+
+    let service = global_data.steamgriddb_service();
+    match kind {
+        AssetKind::Grid => service
+            .fetch_assets_by_game_id(game_id, filter, offset, limit)
+            .await
+            .map(into_assets),
+        AssetKind::Hero => service
+            .fetch_heroes_by_game_id(game_id, filter, offset, limit)
+            .await
+            .map(into_assets),
+        AssetKind::Logo => service
+            .fetch_logos_by_game_id(game_id, filter, offset, limit)
+            .await
+            .map(into_assets),
+        AssetKind::Icon => service
+            .fetch_icons_by_game_id(game_id, filter, offset, limit)
+            .await
+            .map(into_assets),
+    }
+}
@@ -1,23 +1,205 @@
+use std::{collections::BTreeSet, str::FromStr};
+
 use actix_web::{
     get,
     http::StatusCode,
-    web::{Data, Query},
+    web::{self, Data, Query},
 };
 use futures::StreamExt;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    model::{Game, GameList, Response},
-    GlobalState,
+    config::{EnrichmentConfig, Ranking},
+    model::{Asset, EnrichmentStatus, ErrorCode, Game, GameDetails, GameList, Response},
+    routes::assets::fetch_assets,
+    services::{
+        asset_filter::{parse_preferences, AssetFilter, AssetKind, Inclusion},
+        platform::{is_valid_platform_id, Platform},
+        selection::{is_valid_language, Selection},
+    },
+    steamgriddb_models::GameData,
+    ApiError, GlobalState, SteamgriddbService,
 };
 
+/// What `/games` results can be extended with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Include {
+    Logo,
+    Hero,
+    Grid,
+    Icon,
+    /// The platforms each game is known on.
+    Details,
+}
+
+impl Include {
+    const ALL: [Include; 5] = [
+        Include::Logo,
+        Include::Hero,
+        Include::Grid,
+        Include::Icon,
+        Include::Details,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Include::Logo => "logo",
+            Include::Hero => "hero",
+            Include::Grid => "grid",
+            Include::Icon => "icon",
+            Include::Details => "details",
+        }
+    }
+
+    /// The asset kind fetched for this include, if any.
+    fn asset_kind(&self) -> Option<AssetKind> {
+        match self {
+            Include::Logo => Some(AssetKind::Logo),
+            Include::Hero => Some(AssetKind::Hero),
+            Include::Grid => Some(AssetKind::Grid),
+            Include::Icon => Some(AssetKind::Icon),
+            Include::Details => None,
+        }
+    }
+}
+
+impl FromStr for Include {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Include::ALL
+            .into_iter()
+            .find(|include| include.as_str() == s)
+            .ok_or_else(|| {
+                let valid: Vec<&str> = Include::ALL.iter().map(Include::as_str).collect();
+                format!(
+                    "unknown include '{}', expected one of: {}",
+                    s,
+                    valid.join(", ")
+                )
+            })
+    }
+}
+
 #[derive(Deserialize)]
 pub struct SearchGamesQuery {
     pub query: String,
     pub limit: Option<u32>,
     pub offset: Option<u32>,
+    /// Comma separated [`Include`]s, e.g. `logo,hero`.
+    pub include: Option<String>,
+    /// Same as `include=hero`, kept for older clients.
     pub include_hero: Option<bool>,
+    /// Same as `include=logo`, kept for older clients.
     pub include_logo: Option<bool>,
+    /// Same as `include=grid`.
+    pub include_grid: Option<bool>,
+    /// Same as `include=icon`.
+    pub include_icon: Option<bool>,
+    /// Leave out games SteamGridDB has not verified.
+    pub verified_only: Option<bool>,
+    /// `true`, `false` or `any`; enrichment leaves flagged assets out unless the content
+    /// policy allows them or the request opts in.
+    pub nsfw: Option<String>,
+    pub humor: Option<String>,
+    pub epilepsy: Option<String>,
+    /// Overrides of the configured asset selection.
+    pub rank_by: Option<Ranking>,
+    /// Comma separated, best first.
+    pub logo_style: Option<String>,
+    /// Comma separated, best first.
+    pub hero_style: Option<String>,
+    /// Comma separated, best first.
+    pub hero_dimensions: Option<String>,
+    /// Comma separated, best first.
+    pub grid_style: Option<String>,
+    /// Comma separated, best first, e.g. `600x900`.
+    pub grid_dimensions: Option<String>,
+    pub language: Option<String>,
+    pub prefer_static: Option<bool>,
+}
+
+impl SearchGamesQuery {
+    /// The configured selection for assets of `kind`, with the preferences of this
+    /// request applied.
+    fn selection(&self, config: &EnrichmentConfig, kind: AssetKind) -> Result<Selection, String> {
+        let mut selection = Selection::from_config(config, kind);
+        let (styles, dimensions) = match kind {
+            AssetKind::Logo => (&self.logo_style, &None),
+            AssetKind::Hero => (&self.hero_style, &self.hero_dimensions),
+            AssetKind::Grid => (&self.grid_style, &self.grid_dimensions),
+            AssetKind::Icon => (&None, &None),
+        };
+
+        if let Some(styles) = styles {
+            let name = format!("{}_style", kind);
+            selection.styles = parse_preferences(&name, styles, kind.styles())?;
+        }
+        if let Some(dimensions) = dimensions {
+            let name = format!("{}_dimensions", kind);
+            selection.dimensions = parse_preferences(&name, dimensions, kind.dimensions())?;
+        }
+        if let Some(language) = &self.language {
+            if !is_valid_language(language) {
+                return Err(format!(
+                    "invalid language '{}', expected a two or three letter code such as 'en'",
+                    language
+                ));
+            }
+            selection.language = Some(language.clone());
+        }
+        if let Some(rank_by) = self.rank_by {
+            selection.rank_by = rank_by;
+        }
+        if let Some(prefer_static) = self.prefer_static {
+            selection.prefer_static = prefer_static;
+        }
+        Ok(selection)
+    }
+
+    /// Everything asked for with `include` or one of the `include_*` flags.
+    fn includes(&self) -> Result<BTreeSet<Include>, String> {
+        let mut includes = match &self.include {
+            Some(include) => include
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::parse)
+                .collect::<Result<BTreeSet<_>, _>>()?,
+            None => BTreeSet::new(),
+        };
+        for (include, flag) in [
+            (Include::Logo, self.include_logo),
+            (Include::Hero, self.include_hero),
+            (Include::Grid, self.include_grid),
+            (Include::Icon, self.include_icon),
+        ] {
+            if flag.unwrap_or(false) {
+                includes.insert(include);
+            }
+        }
+        Ok(includes)
+    }
+
+    /// The asset kinds to enrich results with, each with its selection. Preferences are
+    /// checked for every kind, asked for or not.
+    fn enrichments(
+        &self,
+        config: &EnrichmentConfig,
+        includes: &BTreeSet<Include>,
+    ) -> Result<Vec<(AssetKind, Selection)>, String> {
+        let mut enrichments = Vec::new();
+        for include in Include::ALL {
+            let Some(kind) = include.asset_kind() else {
+                continue;
+            };
+            let selection = self.selection(config, kind)?;
+            if includes.contains(&include) {
+                enrichments.push((kind, selection));
+            }
+        }
+        Ok(enrichments)
+    }
 }
 
 #[get("/games")]
@@ -25,52 +207,298 @@ pub async fn search_games(
     global_data: Data<GlobalState>,
     query: Query<SearchGamesQuery>,
 ) -> Response<GameList> {
-    let Ok(results) = global_data.search_api(&query.query).await else {
-        return Response::error(
-            "Failed to fetch search results".into(),
-            StatusCode::INTERNAL_SERVER_ERROR,
+    if query.query.trim().is_empty() {
+        return Response::error_with_code(
+            "query must not be empty".into(),
+            StatusCode::BAD_REQUEST,
+            ErrorCode::InvalidQuery,
         );
+    }
+
+    let config = global_data.config();
+    let enrichment = query.includes().and_then(|includes| {
+        let filter = content_filter(&global_data, &query.nsfw, &query.humor, &query.epilepsy)?;
+        let enrichments = query.enrichments(&config.enrichment, &includes)?;
+        Ok((includes.contains(&Include::Details), filter, enrichments))
+    });
+    let (details, filter, enrichments) = match enrichment {
+        Ok(enrichment) => enrichment,
+        Err(message) => {
+            return Response::error_with_code(
+                message,
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidQuery,
+            )
+        }
     };
 
-    let is_final =
-        results.len() <= query.limit.unwrap_or(15) as usize + query.offset.unwrap_or(0) as usize;
+    let mut results = match global_data.search_api(&query.query).await {
+        Ok(results) => results,
+        Err(err) => return err.into(),
+    };
+    if query.verified_only.unwrap_or(false) {
+        results.retain(|result| result.verified);
+    }
 
     let offset = query.offset.unwrap_or(0) as usize;
-    let limit = query.limit.unwrap_or(15) as usize;
+    let limit = config.pagination.limit(query.limit) as usize;
+
+    let is_final = results.len() <= limit + offset;
 
     let mut games: Vec<Game> = results
         .into_iter()
         .skip(offset)
         .take(limit)
-        .map(|res| res.into())
+        .map(|res| {
+            let types = details.then(|| res.types.clone());
+            Game {
+                types,
+                ..res.into()
+            }
+        })
         .collect();
 
-    let include_logo = query.include_logo.unwrap_or(false);
-    let include_hero = query.include_hero.unwrap_or(false);
-
-    /* Parallelize logo/hero fetching */
+    /* Parallelize asset fetching */
     futures::stream::iter(games.iter_mut())
         .map(|game| {
             let global = global_data.clone();
+            let filter = &filter;
+            let enrichments = &enrichments;
             async move {
                 // create a per-task service instance (cheap, clones Arcs)
                 let service = global.steamgriddb_service();
 
-                if include_logo {
-                    if let Ok(Some(url)) = service.get_first_logo_by_game_id(game.id).await {
-                        game.logo_url = Some(url);
-                    }
-                }
-                if include_hero {
-                    if let Ok(Some(url)) = service.get_first_hero_by_game_id(game.id).await {
-                        game.hero_url = Some(url);
-                    }
+                let metrics = global.metrics();
+
+                for (kind, selection) in enrichments {
+                    let url = best_asset_url(&service, *kind, game.id, filter, selection).await;
+                    metrics.observe_enrichment(
+                        (*kind).into(),
+                        url.as_ref().map(Option::is_some).map_err(|_| ()),
+                    );
+                    let game_id = game.id;
+                    let (slot, status) = enrichment_slots(game, *kind);
+                    *status = Some(match url {
+                        Ok(Some(url)) => {
+                            *slot = Some(url);
+                            EnrichmentStatus::Found
+                        }
+                        Ok(None) => EnrichmentStatus::None,
+                        Err(err) => {
+                            tracing::warn!(game_id, %kind, error = %err, "enrichment failed");
+                            EnrichmentStatus::Error
+                        }
+                    });
                 }
             }
         })
-        .buffer_unordered(8)
+        .buffer_unordered(config.enrichment.concurrency)
         .for_each(|_| async {})
         .await;
 
-    Response::success(GameList { games, is_final })
+    let partial = games.iter().any(Game::enrichment_failed);
+    Response::success(GameList {
+        games,
+        is_final,
+        partial,
+    })
+}
+
+/// The url of the asset of `kind` `selection` picks for a game.
+async fn best_asset_url(
+    service: &SteamgriddbService,
+    kind: AssetKind,
+    game_id: usize,
+    filter: &AssetFilter,
+    selection: &Selection,
+) -> Result<Option<String>, ApiError> {
+    match kind {
+        AssetKind::Grid => {
+            service
+                .get_best_grid_by_game_id(game_id, filter, selection)
+                .await
+        }
+        AssetKind::Hero => {
+            service
+                .get_best_hero_by_game_id(game_id, filter, selection)
+                .await
+        }
+        AssetKind::Logo => {
+            service
+                .get_best_logo_by_game_id(game_id, filter, selection)
+                .await
+        }
+        AssetKind::Icon => {
+            service
+                .get_best_icon_by_game_id(game_id, filter, selection)
+                .await
+        }
+    }
+}
+
+/// The fields of `game` holding the url of its asset of `kind` and how looking it up went.
+fn enrichment_slots(
+    game: &mut Game,
+    kind: AssetKind,
+) -> (&mut Option<String>, &mut Option<EnrichmentStatus>) {
+    match kind {
+        AssetKind::Grid => (&mut game.grid_url, &mut game.grid_status),
+        AssetKind::Hero => (&mut game.hero_url, &mut game.hero_status),
+        AssetKind::Logo => (&mut game.logo_url, &mut game.logo_status),
+        AssetKind::Icon => (&mut game.icon_url, &mut game.icon_status),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct GameDetailsQuery {
+    pub include_grid: Option<bool>,
+    pub include_hero: Option<bool>,
+    pub include_logo: Option<bool>,
+    pub include_icon: Option<bool>,
+    pub nsfw: Option<String>,
+    pub humor: Option<String>,
+    pub epilepsy: Option<String>,
+}
+
+#[get("/games/{game_id}")]
+pub async fn get_game_by_id(
+    global_data: Data<GlobalState>,
+    path: web::Path<usize>,
+    query: Query<GameDetailsQuery>,
+) -> Response<GameDetails> {
+    let filter = match content_filter(&global_data, &query.nsfw, &query.humor, &query.epilepsy) {
+        Ok(filter) => filter,
+        Err(message) => {
+            return Response::error_with_code(
+                message,
+                StatusCode::BAD_REQUEST,
+                ErrorCode::InvalidQuery,
+            )
+        }
+    };
+
+    let game_id = path.into_inner();
+    let game = match global_data
+        .steamgriddb_service()
+        .get_game_by_id(game_id)
+        .await
+    {
+        Ok(game) => game,
+        Err(err) => return err.into(),
+    };
+
+    let mut details = GameDetails::from(game);
+    let first = |kind, include: Option<bool>| {
+        first_asset(
+            &global_data,
+            kind,
+            game_id,
+            &filter,
+            include.unwrap_or(false),
+        )
+    };
+    let (grid, hero, logo, icon) = futures::join!(
+        first(AssetKind::Grid, query.include_grid),
+        first(AssetKind::Hero, query.include_hero),
+        first(AssetKind::Logo, query.include_logo),
+        first(AssetKind::Icon, query.include_icon),
+    );
+    (details.grid, details.grid_status) = grid;
+    (details.hero, details.hero_status) = hero;
+    (details.logo, details.logo_status) = logo;
+    (details.icon, details.icon_status) = icon;
+    details.partial = [
+        details.grid_status,
+        details.hero_status,
+        details.logo_status,
+        details.icon_status,
+    ]
+    .contains(&Some(EnrichmentStatus::Error));
+
+    Response::success(details)
+}
+
+/// The first asset of `kind` SteamGridDB lists for a game and how looking it up went, if
+/// asked for. Like enrichment of search results, a failure leaves the asset out and is
+/// reported as [`EnrichmentStatus::Error`] instead of failing the request.
+async fn first_asset(
+    global_data: &GlobalState,
+    kind: AssetKind,
+    game_id: usize,
+    filter: &AssetFilter,
+    include: bool,
+) -> (Option<Asset>, Option<EnrichmentStatus>) {
+    if !include {
+        return (None, None);
+    }
+
+    match fetch_assets(global_data, kind, game_id, filter, 0, 1).await {
+        Ok(listing) => match listing.data.into_iter().next() {
+            Some(asset) => (Some(asset), Some(EnrichmentStatus::Found)),
+            None => (None, Some(EnrichmentStatus::None)),
+        },
+        Err(err) => {
+            tracing::warn!(game_id, %kind, error = %err, "fetching embedded asset failed");
+            (None, Some(EnrichmentStatus::Error))
+        }
+    }
+}
+
+/// The content flags a request opted into, with the server's content policy applied.
+fn content_filter(
+    global_data: &GlobalState,
+    nsfw: &Option<String>,
+    humor: &Option<String>,
+    epilepsy: &Option<String>,
+) -> Result<AssetFilter, String> {
+    AssetFilter {
+        nsfw: Inclusion::parse_opt("nsfw", nsfw.as_deref())?,
+        humor: Inclusion::parse_opt("humor", humor.as_deref())?,
+        epilepsy: Inclusion::parse_opt("epilepsy", epilepsy.as_deref())?,
+        ..AssetFilter::default()
+    }
+    .with_policy(&global_data.config().content)
+}
+
+/// Finds the game SteamGridDB knows under a store id, e.g. a Steam app id.
+#[get("/games/platform/{platform}/{id}")]
+pub async fn get_game_by_platform_id(
+    global_data: Data<GlobalState>,
+    path: web::Path<(Platform, String)>,
+) -> Response<Game> {
+    let (platform, id) = path.into_inner();
+    match lookup_platform_game(&global_data, platform, &id).await {
+        Ok(game) => Response::success(game.into()),
+        Err(response) => response,
+    }
+}
+
+/// Resolves a store id through SteamGridDB, answering malformed ids with a 400 and
+/// unknown ones with a 404.
+pub(super) async fn lookup_platform_game<T: Serialize>(
+    global_data: &GlobalState,
+    platform: Platform,
+    id: &str,
+) -> Result<GameData, Response<T>> {
+    if !is_valid_platform_id(id) {
+        return Err(Response::error_with_code(
+            format!("'{}' is not a valid {} id", id, platform),
+            StatusCode::BAD_REQUEST,
+            ErrorCode::InvalidQuery,
+        ));
+    }
+
+    match global_data
+        .steamgriddb_service()
+        .get_game_by_platform_id(platform, id)
+        .await
+    {
+        Ok(game) => Ok(game),
+        Err(ApiError::UpstreamStatus { status: 404, .. }) => Err(Response::error_with_code(
+            format!("No game with {} id {}", platform, id),
+            StatusCode::NOT_FOUND,
+            ErrorCode::GameNotFound,
+        )),
+        Err(err) => Err(err.into()),
+    }
 }
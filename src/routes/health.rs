@@ -0,0 +1,130 @@
+use actix_web::{get, http::StatusCode, web::Data};
+use serde::Serialize;
+
+use crate::{
+    model::{ErrorCode, Response},
+    services::circuit_breaker::CircuitState,
+    GlobalState,
+};
+
+#[derive(Serialize)]
+pub struct Liveness {
+    pub status: &'static str,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentState {
+    Ok,
+    Disabled,
+    /// Not checked yet.
+    Unknown,
+    /// Not working, but the service can still answer requests.
+    Degraded,
+    /// Not working; the service should not receive traffic.
+    Failed,
+}
+
+#[derive(Serialize)]
+pub struct ComponentStatus {
+    pub status: ComponentState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checked_secs_ago: Option<u64>,
+}
+
+impl ComponentStatus {
+    fn new(status: ComponentState) -> Self {
+        Self {
+            status,
+            message: None,
+            checked_secs_ago: None,
+        }
+    }
+
+    fn with_message(status: ComponentState, message: String) -> Self {
+        Self {
+            message: Some(message),
+            ..Self::new(status)
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub config: ComponentStatus,
+    pub cache: ComponentStatus,
+    pub persistent_cache: ComponentStatus,
+    pub upstream: ComponentStatus,
+}
+
+/// Answers as long as the process is serving requests.
+#[get("/health/live")]
+pub async fn liveness() -> Response<Liveness> {
+    Response::success(Liveness { status: "ok" })
+}
+
+/// Reports whether the service can take traffic. SteamGridDB being unreachable only
+/// degrades readiness: the caches may still answer, and restarting would not help.
+#[get("/health/ready")]
+pub async fn readiness(global_data: Data<GlobalState>) -> Response<Readiness> {
+    let service = global_data.steamgriddb_service();
+
+    let cache = match global_data.cache() {
+        Some(_) => ComponentStatus::new(ComponentState::Ok),
+        None => ComponentStatus::new(ComponentState::Disabled),
+    };
+
+    let persistent_cache = match service.persistent_cache() {
+        Some(store) => match store.check().await {
+            Ok(()) => ComponentStatus::new(ComponentState::Ok),
+            Err(message) => ComponentStatus::with_message(ComponentState::Failed, message),
+        },
+        None => ComponentStatus::new(ComponentState::Disabled),
+    };
+
+    let probe = global_data.upstream_probe();
+    probe.refresh_if_due(service.clone());
+    let mut upstream = match probe.last() {
+        Some(last) => ComponentStatus {
+            checked_secs_ago: Some(last.checked_at.elapsed().as_secs()),
+            ..match last.error {
+                Some(message) => ComponentStatus::with_message(ComponentState::Degraded, message),
+                None => ComponentStatus::new(ComponentState::Ok),
+            }
+        },
+        None => ComponentStatus::new(ComponentState::Unknown),
+    };
+    if global_data
+        .circuit_status()
+        .is_some_and(|status| status.state == CircuitState::Open)
+    {
+        upstream.status = ComponentState::Degraded;
+        upstream.message = Some("circuit breaker open".into());
+    }
+
+    let config = ComponentStatus::new(ComponentState::Ok);
+    let ready = [&config, &cache, &persistent_cache]
+        .iter()
+        .all(|component| component.status != ComponentState::Failed);
+    let report = Readiness {
+        ready,
+        config,
+        cache,
+        persistent_cache,
+        upstream,
+    };
+
+    if ready {
+        Response::success(report)
+    } else {
+        Response::error_with_code(
+            "service is not ready".into(),
+            StatusCode::SERVICE_UNAVAILABLE,
+            ErrorCode::NotReady,
+        )
+        .with_data(report)
+    }
+}
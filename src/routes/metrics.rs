@@ -0,0 +1,11 @@
+use actix_web::{get, web::Data, HttpResponse};
+
+use crate::GlobalState;
+
+/// Prometheus scrape endpoint, in the text exposition format.
+#[get("/metrics")]
+pub async fn prometheus_metrics(global_data: Data<GlobalState>) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(global_data.metrics().render())
+}
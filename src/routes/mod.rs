@@ -1,5 +1,48 @@
+mod admin;
 mod assets;
 mod games;
+mod health;
+mod metrics;
+mod status;
 
-pub use assets::get_assets_by_id;
-pub use games::search_games;
+use actix_web::{
+    error::{InternalError, PathError, QueryPayloadError},
+    http::StatusCode,
+    web::{PathConfig, QueryConfig},
+    HttpRequest, Responder,
+};
+
+use crate::model::{ErrorCode, Response};
+
+pub use admin::refresh_game;
+pub use assets::{get_assets_by_id, get_assets_by_platform_id};
+pub use games::{get_game_by_id, get_game_by_platform_id, search_games};
+pub use health::{liveness, readiness};
+pub use metrics::prometheus_metrics;
+pub use status::upstream_status;
+
+/// Query extractor config answering malformed query strings with a `400 INVALID_QUERY`
+/// [`Response`] instead of actix' plain-text default.
+pub fn query_config() -> QueryConfig {
+    QueryConfig::default().error_handler(|err: QueryPayloadError, req: &HttpRequest| {
+        invalid_request(err.to_string(), req, err)
+    })
+}
+
+/// Path extractor config answering unparsable path segments (e.g. a non-numeric game id)
+/// with a `400 INVALID_QUERY` [`Response`].
+pub fn path_config() -> PathConfig {
+    PathConfig::default().error_handler(|err: PathError, req: &HttpRequest| {
+        invalid_request(err.to_string(), req, err)
+    })
+}
+
+fn invalid_request<E>(message: String, req: &HttpRequest, err: E) -> actix_web::Error
+where
+    E: std::fmt::Debug + std::fmt::Display + 'static,
+{
+    let response =
+        Response::<()>::error_with_code(message, StatusCode::BAD_REQUEST, ErrorCode::InvalidQuery)
+            .respond_to(req);
+    InternalError::from_response(err, response).into()
+}
@@ -0,0 +1,18 @@
+use actix_web::{get, web::Data};
+use serde::Serialize;
+
+use crate::{model::Response, services::circuit_breaker::CircuitStatus, GlobalState};
+
+#[derive(Serialize)]
+pub struct UpstreamStatus {
+    /// `None` when the circuit breaker is disabled.
+    pub circuit_breaker: Option<CircuitStatus>,
+}
+
+/// Reports whether SteamGridDB is currently being called or short-circuited.
+#[get("/status/upstream")]
+pub async fn upstream_status(global_data: Data<GlobalState>) -> Response<UpstreamStatus> {
+    Response::success(UpstreamStatus {
+        circuit_breaker: global_data.circuit_status(),
+    })
+}
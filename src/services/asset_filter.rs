@@ -0,0 +1,290 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::config::{ContentPolicyConfig, ContentRule};
+use crate::services::endpoint::Endpoint;
+
+/// Grid styles accepted by SteamGridDB.
+pub const GRID_STYLES: &[&str] = &["alternate", "blurred", "white_logo", "material", "no_logo"];
+
+/// Grid dimensions accepted by SteamGridDB.
+pub const GRID_DIMENSIONS: &[&str] = &[
+    "460x215",
+    "920x430",
+    "600x900",
+    "342x482",
+    "660x930",
+    "512x512",
+    "1024x1024",
+];
+
+/// Grid file types, as the short names used in our query strings.
+pub const GRID_MIMES: &[&str] = &["png", "jpeg", "webp"];
+
+pub const HERO_STYLES: &[&str] = &["alternate", "blurred", "material"];
+pub const HERO_DIMENSIONS: &[&str] = &["1920x620", "3840x1240", "1600x650"];
+pub const HERO_MIMES: &[&str] = &["png", "jpeg", "webp"];
+
+pub const LOGO_STYLES: &[&str] = &["official", "white", "black", "custom"];
+pub const LOGO_MIMES: &[&str] = &["png", "webp"];
+
+pub const ICON_STYLES: &[&str] = &["official", "custom"];
+pub const ICON_DIMENSIONS: &[&str] = &[
+    "16x16",
+    "20x20",
+    "24x24",
+    "28x28",
+    "32x32",
+    "40x40",
+    "48x48",
+    "54x54",
+    "57x57",
+    "60x60",
+    "64x64",
+    "72x72",
+    "76x76",
+    "80x80",
+    "96x96",
+    "114x114",
+    "120x120",
+    "128x128",
+    "144x144",
+    "152x152",
+    "160x160",
+    "180x180",
+    "192x192",
+    "194x194",
+    "256x256",
+    "512x512",
+    "768x768",
+    "1024x1024",
+];
+
+/// `icon` stands for `image/vnd.microsoft.icon`.
+pub const ICON_MIMES: &[&str] = &["png", "icon"];
+
+/// The kinds of assets SteamGridDB lists per game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssetKind {
+    #[default]
+    Grid,
+    Hero,
+    Logo,
+    Icon,
+}
+
+impl AssetKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssetKind::Grid => "grid",
+            AssetKind::Hero => "hero",
+            AssetKind::Logo => "logo",
+            AssetKind::Icon => "icon",
+        }
+    }
+
+    pub fn endpoint(&self) -> Endpoint {
+        match self {
+            AssetKind::Grid => Endpoint::Grids,
+            AssetKind::Hero => Endpoint::Heroes,
+            AssetKind::Logo => Endpoint::Logos,
+            AssetKind::Icon => Endpoint::Icons,
+        }
+    }
+
+    pub fn styles(&self) -> &'static [&'static str] {
+        match self {
+            AssetKind::Grid => GRID_STYLES,
+            AssetKind::Hero => HERO_STYLES,
+            AssetKind::Logo => LOGO_STYLES,
+            AssetKind::Icon => ICON_STYLES,
+        }
+    }
+
+    /// Empty for logos, which cannot be filtered by size.
+    pub fn dimensions(&self) -> &'static [&'static str] {
+        match self {
+            AssetKind::Grid => GRID_DIMENSIONS,
+            AssetKind::Hero => HERO_DIMENSIONS,
+            AssetKind::Logo => &[],
+            AssetKind::Icon => ICON_DIMENSIONS,
+        }
+    }
+
+    pub fn mimes(&self) -> &'static [&'static str] {
+        match self {
+            AssetKind::Grid => GRID_MIMES,
+            AssetKind::Hero => HERO_MIMES,
+            AssetKind::Logo => LOGO_MIMES,
+            AssetKind::Icon => ICON_MIMES,
+        }
+    }
+}
+
+impl fmt::Display for AssetKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+const INCLUSIONS: &[&str] = &["true", "false", "any"];
+
+/// Whether assets with a content flag (nsfw, humor, epilepsy) are returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inclusion {
+    /// Only flagged assets.
+    Only,
+    /// No flagged assets.
+    Exclude,
+    /// Flagged and unflagged assets.
+    Any,
+}
+
+impl Inclusion {
+    /// Parses `true`, `false` or `any`, as SteamGridDB spells them.
+    pub fn parse(name: &str, value: &str) -> Result<Self, String> {
+        match value {
+            "true" => Ok(Inclusion::Only),
+            "false" => Ok(Inclusion::Exclude),
+            "any" => Ok(Inclusion::Any),
+            _ => Err(not_allowed(name, value, INCLUSIONS)),
+        }
+    }
+
+    /// Like [`Inclusion::parse`], for an optional query parameter.
+    pub fn parse_opt(name: &str, value: Option<&str>) -> Result<Option<Self>, String> {
+        value.map(|value| Inclusion::parse(name, value)).transpose()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Inclusion::Only => "true",
+            Inclusion::Exclude => "false",
+            Inclusion::Any => "any",
+        }
+    }
+}
+
+/// Filters forwarded to SteamGridDB when listing assets of a game.
+///
+/// Unset filters are left out of the request, so SteamGridDB's defaults apply.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssetFilter {
+    pub styles: Vec<String>,
+    pub dimensions: Vec<String>,
+    /// Short file types, e.g. `png`.
+    pub mimes: Vec<String>,
+    /// `Some(true)` for animated assets only, `Some(false)` for static ones only.
+    pub animated: Option<bool>,
+    pub nsfw: Option<Inclusion>,
+    pub humor: Option<Inclusion>,
+    pub epilepsy: Option<Inclusion>,
+}
+
+impl AssetFilter {
+    /// The filters as SteamGridDB query parameters.
+    pub fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if !self.styles.is_empty() {
+            query.push(("styles", self.styles.join(",")));
+        }
+        if !self.dimensions.is_empty() {
+            query.push(("dimensions", self.dimensions.join(",")));
+        }
+        if !self.mimes.is_empty() {
+            let mimes: Vec<String> = self.mimes.iter().map(|mime| mime_type(mime)).collect();
+            query.push(("mimes", mimes.join(",")));
+        }
+        if let Some(animated) = self.animated {
+            let types = if animated { "animated" } else { "static" };
+            query.push(("types", types.to_string()));
+        }
+        for (name, inclusion) in [
+            ("nsfw", self.nsfw),
+            ("humor", self.humor),
+            ("epilepsy", self.epilepsy),
+        ] {
+            if let Some(inclusion) = inclusion {
+                query.push((name, inclusion.as_str().to_string()));
+            }
+        }
+        query
+    }
+
+    /// Applies the server's content policy to the content flags a request asked for.
+    /// Flags left unset are excluded unless the policy allows them, and asking for
+    /// blocked content is an error.
+    pub fn with_policy(mut self, policy: &ContentPolicyConfig) -> Result<Self, String> {
+        for (name, rule, inclusion) in [
+            ("nsfw", policy.nsfw, &mut self.nsfw),
+            ("humor", policy.humor, &mut self.humor),
+            ("epilepsy", policy.epilepsy, &mut self.epilepsy),
+        ] {
+            *inclusion = match (rule, *inclusion) {
+                (ContentRule::Block, Some(Inclusion::Only | Inclusion::Any)) => {
+                    return Err(format!("{} assets are disabled on this server", name))
+                }
+                (ContentRule::Block | ContentRule::OptIn, None) => Some(Inclusion::Exclude),
+                (_, requested) => requested,
+            };
+        }
+        Ok(self)
+    }
+}
+
+impl fmt::Display for AssetFilter {
+    /// The query string of [`AssetFilter::query`], used to tell cached results apart.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, value)) in self.query().iter().enumerate() {
+            if i > 0 {
+                f.write_str("&")?;
+            }
+            write!(f, "{}={}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a comma separated list of values out of `allowed`. The result is sorted and
+/// deduplicated, so equivalent filters share cache entries.
+pub fn parse_list(name: &str, value: &str, allowed: &[&str]) -> Result<Vec<String>, String> {
+    let mut values = parse_preferences(name, value, allowed)?;
+    values.sort();
+    Ok(values)
+}
+
+/// Like [`parse_list`], but keeps the values in the order given, best first.
+pub fn parse_preferences(name: &str, value: &str, allowed: &[&str]) -> Result<Vec<String>, String> {
+    if allowed.is_empty() {
+        return Err(format!("{} is not supported for this asset type", name));
+    }
+
+    let mut values: Vec<String> = Vec::new();
+    for v in value.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+        if !allowed.contains(&v) {
+            return Err(not_allowed(name, v, allowed));
+        }
+        if !values.iter().any(|seen| seen == v) {
+            values.push(v.to_string());
+        }
+    }
+    Ok(values)
+}
+
+fn mime_type(short: &str) -> String {
+    match short {
+        "icon" => "image/vnd.microsoft.icon".to_string(),
+        _ => format!("image/{}", short),
+    }
+}
+
+fn not_allowed(name: &str, value: &str, allowed: &[&str]) -> String {
+    format!(
+        "invalid {} '{}', expected one of: {}",
+        name,
+        value,
+        allowed.join(", ")
+    )
+}
@@ -0,0 +1,201 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use steamgriddb_api::search::SearchResult;
+
+use crate::{
+    config::{CacheConfig, CacheKindConfig},
+    steamgriddb_models::{
+        GridResponseData, HeroResponseData, IconResponseData, Listing, LogoResposeData,
+    },
+};
+
+/// Snapshot of a cache's counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+struct CacheEntry<V> {
+    value: V,
+    expires_at: Instant,
+}
+
+/// A size-bounded map whose entries expire after a fixed time to live.
+///
+/// When full, expired entries are dropped first; if none are expired, the entry
+/// closest to expiry is evicted.
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<K, CacheEntry<V>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().expect("cache lock poisoned");
+
+        let value = match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        };
+
+        let counter = if value.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        value
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        self.insert_with_ttl(key, value, self.ttl);
+    }
+
+    /// Inserts `value` with a time to live other than the cache's default.
+    pub fn insert_with_ttl(&self, key: K, value: V, ttl: Duration) {
+        if self.max_entries == 0 || ttl.is_zero() {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut entries = self.entries.lock().expect("cache lock poisoned");
+
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            entries.retain(|_, entry| entry.expires_at > now);
+        }
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            let soonest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.expires_at)
+                .map(|(key, _)| key.clone());
+            if let Some(soonest) = soonest {
+                entries.remove(&soonest);
+            }
+        }
+
+        entries.insert(
+            key,
+            CacheEntry {
+                value,
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    pub fn remove(&self, key: &K) {
+        self.entries
+            .lock()
+            .expect("cache lock poisoned")
+            .remove(key);
+    }
+
+    /// Removes every entry whose key matches `predicate`.
+    pub fn remove_where(&self, predicate: impl Fn(&K) -> bool) {
+        self.entries
+            .lock()
+            .expect("cache lock poisoned")
+            .retain(|key, _| !predicate(key));
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.lock().expect("cache lock poisoned").len(),
+        }
+    }
+}
+
+/// The per-kind caches sitting in front of SteamGridDB.
+///
+/// `grids`, `hero_lists`, `logo_lists` and `icon_lists` hold single upstream pages, keyed
+/// by game id and the filters and page they were fetched with. Empty pages are kept for
+/// `negative_ttl` only.
+/// `logos`, `heroes` and `icons` are keyed by game id and the filters the asset was picked
+/// with.
+/// They also remember games that have no such asset (`None`), for `negative_ttl` rather
+/// than the kind's regular time to live.
+pub struct UpstreamCache {
+    pub search: TtlCache<String, Vec<SearchResult>>,
+    pub grids: TtlCache<(usize, String), Listing<GridResponseData>>,
+    pub hero_lists: TtlCache<(usize, String), Listing<HeroResponseData>>,
+    pub logo_lists: TtlCache<(usize, String), Listing<LogoResposeData>>,
+    pub icon_lists: TtlCache<(usize, String), Listing<IconResponseData>>,
+    pub logos: TtlCache<(usize, String), Option<String>>,
+    pub heroes: TtlCache<(usize, String), Option<String>>,
+    pub icons: TtlCache<(usize, String), Option<String>>,
+    pub negative_ttl: Duration,
+}
+
+impl UpstreamCache {
+    pub fn new(config: &CacheConfig) -> Self {
+        fn cache<K: Eq + Hash + Clone, V: Clone>(config: &CacheKindConfig) -> TtlCache<K, V> {
+            TtlCache::new(Duration::from_secs(config.ttl_secs), config.max_entries)
+        }
+
+        Self {
+            search: cache(&config.search),
+            grids: cache(&config.grids),
+            hero_lists: cache(&config.hero_lists),
+            logo_lists: cache(&config.logo_lists),
+            icon_lists: cache(&config.icon_lists),
+            logos: cache(&config.logos),
+            heroes: cache(&config.heroes),
+            icons: cache(&config.icons),
+            negative_ttl: Duration::from_secs(config.negative_ttl_secs),
+        }
+    }
+
+    /// Forgets everything cached for `game_id`, including remembered missing assets.
+    pub fn invalidate_game(&self, game_id: usize) {
+        self.grids.remove_where(|(id, _)| *id == game_id);
+        self.hero_lists.remove_where(|(id, _)| *id == game_id);
+        self.logo_lists.remove_where(|(id, _)| *id == game_id);
+        self.icon_lists.remove_where(|(id, _)| *id == game_id);
+        self.logos.remove_where(|(id, _)| *id == game_id);
+        self.heroes.remove_where(|(id, _)| *id == game_id);
+        self.icons.remove_where(|(id, _)| *id == game_id);
+    }
+
+    /// Counters for every cache kind, labelled by kind.
+    pub fn stats(&self) -> [(&'static str, CacheStats); 8] {
+        [
+            ("search", self.search.stats()),
+            ("grids", self.grids.stats()),
+            ("hero_lists", self.hero_lists.stats()),
+            ("logo_lists", self.logo_lists.stats()),
+            ("icon_lists", self.icon_lists.stats()),
+            ("logos", self.logos.stats()),
+            ("heroes", self.heroes.stats()),
+            ("icons", self.icons.stats()),
+        ]
+    }
+}
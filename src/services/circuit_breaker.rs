@@ -0,0 +1,154 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use crate::config::CircuitBreakerConfig;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests flow normally.
+    Closed,
+    /// Upstream is considered down; requests fail fast.
+    Open,
+    /// The open period is over; a single probe request decides whether to close again.
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+}
+
+/// Point-in-time view of a [`CircuitBreaker`].
+#[derive(Serialize, Debug, Clone)]
+pub struct CircuitStatus {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub times_opened: u64,
+    /// Milliseconds until the next probe is let through, while open.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_in_ms: Option<u64>,
+}
+
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    times_opened: u64,
+    opened_at: Option<Instant>,
+    probe_started_at: Option<Instant>,
+}
+
+/// Stops calling SteamGridDB after `failure_threshold` consecutive outage-type failures.
+///
+/// After `open_duration` one probe request is let through; its outcome either closes the
+/// circuit or opens it for another period. A probe that never reports back (e.g. because
+/// its request was cancelled) is replaced after another `open_duration`.
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            open_duration,
+            state: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                times_opened: 0,
+                opened_at: None,
+                probe_started_at: None,
+            }),
+        }
+    }
+
+    pub fn from_config(config: &CircuitBreakerConfig) -> Self {
+        Self::new(
+            config.failure_threshold,
+            Duration::from_secs(config.open_secs),
+        )
+    }
+
+    /// Whether a request may be sent now.
+    pub fn allow_request(&self) -> bool {
+        let mut breaker = self.state.lock().expect("circuit breaker lock poisoned");
+        let now = Instant::now();
+
+        match breaker.state {
+            CircuitState::Closed => true,
+            CircuitState::Open => {
+                let reopen_at = breaker.opened_at.map(|at| at + self.open_duration);
+                if reopen_at.is_some_and(|at| now < at) {
+                    return false;
+                }
+                breaker.state = CircuitState::HalfOpen;
+                breaker.probe_started_at = Some(now);
+                true
+            }
+            CircuitState::HalfOpen => {
+                let probe_expired = breaker
+                    .probe_started_at
+                    .is_none_or(|at| now >= at + self.open_duration);
+                if probe_expired {
+                    breaker.probe_started_at = Some(now);
+                }
+                probe_expired
+            }
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut breaker = self.state.lock().expect("circuit breaker lock poisoned");
+        breaker.state = CircuitState::Closed;
+        breaker.consecutive_failures = 0;
+        breaker.opened_at = None;
+        breaker.probe_started_at = None;
+    }
+
+    pub fn record_failure(&self) {
+        let mut breaker = self.state.lock().expect("circuit breaker lock poisoned");
+        breaker.consecutive_failures = breaker.consecutive_failures.saturating_add(1);
+
+        let trips = match breaker.state {
+            CircuitState::Closed => breaker.consecutive_failures >= self.failure_threshold,
+            CircuitState::HalfOpen => true,
+            CircuitState::Open => false,
+        };
+        if trips {
+            breaker.state = CircuitState::Open;
+            breaker.times_opened += 1;
+            breaker.opened_at = Some(Instant::now());
+            breaker.probe_started_at = None;
+        }
+    }
+
+    pub fn status(&self) -> CircuitStatus {
+        let breaker = self.state.lock().expect("circuit breaker lock poisoned");
+        let retry_in_ms = match (breaker.state, breaker.opened_at) {
+            (CircuitState::Open, Some(opened_at)) => Some(
+                (opened_at + self.open_duration)
+                    .saturating_duration_since(Instant::now())
+                    .as_millis() as u64,
+            ),
+            _ => None,
+        };
+
+        CircuitStatus {
+            state: breaker.state,
+            consecutive_failures: breaker.consecutive_failures,
+            times_opened: breaker.times_opened,
+            retry_in_ms,
+        }
+    }
+}
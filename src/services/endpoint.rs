@@ -0,0 +1,31 @@
+use std::fmt;
+
+/// The SteamGridDB endpoint an upstream request was made against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    Search,
+    Games,
+    Grids,
+    Logos,
+    Heroes,
+    Icons,
+}
+
+impl Endpoint {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Endpoint::Search => "search",
+            Endpoint::Games => "games",
+            Endpoint::Grids => "grids",
+            Endpoint::Logos => "logos",
+            Endpoint::Heroes => "heroes",
+            Endpoint::Icons => "icons",
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
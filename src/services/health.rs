@@ -0,0 +1,66 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::services::steamgriddb_service::SteamgriddbService;
+
+/// Outcome of the last upstream probe.
+#[derive(Debug, Clone)]
+pub struct ProbeResult {
+    pub checked_at: Instant,
+    /// Why SteamGridDB could not be reached; `None` if it answered.
+    pub error: Option<String>,
+}
+
+/// Remembers whether SteamGridDB was reachable, so health checks can report it without
+/// calling upstream on every request.
+///
+/// A new probe is started in the background at most once per `interval`.
+pub struct UpstreamProbe {
+    interval: Duration,
+    last: Mutex<Option<ProbeResult>>,
+    running: AtomicBool,
+}
+
+impl UpstreamProbe {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last: Mutex::new(None),
+            running: AtomicBool::new(false),
+        }
+    }
+
+    pub fn last(&self) -> Option<ProbeResult> {
+        self.last.lock().expect("probe lock poisoned").clone()
+    }
+
+    /// Probes SteamGridDB in the background if the last result is older than the interval.
+    pub fn refresh_if_due(self: &Arc<Self>, service: SteamgriddbService) {
+        let due = self
+            .last()
+            .is_none_or(|last| last.checked_at.elapsed() >= self.interval);
+        if !due || self.running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let this = self.clone();
+        tokio::spawn(async move {
+            this.run(&service).await;
+            this.running.store(false, Ordering::SeqCst);
+        });
+    }
+
+    /// Probes SteamGridDB now and records the outcome.
+    pub async fn run(&self, service: &SteamgriddbService) {
+        let error = service.probe().await.err().map(|err| err.to_string());
+        *self.last.lock().expect("probe lock poisoned") = Some(ProbeResult {
+            checked_at: Instant::now(),
+            error,
+        });
+    }
+}
@@ -1 +1,12 @@
+pub mod asset_filter;
+pub mod cache;
+pub mod circuit_breaker;
+pub mod endpoint;
+pub mod health;
+pub mod persistent_cache;
+pub mod platform;
+pub mod rate_limiter;
+pub mod retry;
+pub mod selection;
+pub mod single_flight;
 pub mod steamgriddb_service;
@@ -0,0 +1,202 @@
+#![allow(clippy::result_large_err)] // redb::Error is large, but it never leaves this module
+
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use redb::{Database, ReadableTable, TableDefinition};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{config::PersistentCacheConfig, error::ApiError};
+
+/// Version of the persisted payloads. Bump whenever the cached upstream types or the
+/// `Game`/`Asset` models derived from them change shape; older entries are then dropped.
+pub const SCHEMA_VERSION: u32 = 3;
+
+const ENTRIES: TableDefinition<&str, &[u8]> = TableDefinition::new("entries");
+const META: TableDefinition<&str, u32> = TableDefinition::new("meta");
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+#[derive(Serialize, Deserialize)]
+struct StoredEntry<V> {
+    version: u32,
+    stored_at: u64,
+    /// Time to live of this entry alone, which is never served stale.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ttl_secs: Option<u64>,
+    value: V,
+}
+
+/// Outcome of a persistent cache lookup.
+pub enum Lookup<V> {
+    /// The entry is younger than the time to live.
+    Fresh(V),
+    /// The entry is past its time to live but may still be served while it is refreshed.
+    Stale(V),
+    Miss,
+}
+
+/// An on-disk cache for upstream responses that survives restarts.
+///
+/// Storage failures are never surfaced to callers: a broken read is a miss and a broken
+/// write is dropped, so the service keeps working against SteamGridDB directly.
+pub struct PersistentCache {
+    db: Arc<Database>,
+    ttl: Duration,
+    stale_ttl: Duration,
+}
+
+impl PersistentCache {
+    pub fn open(config: &PersistentCacheConfig) -> Result<Self, ApiError> {
+        let db = Database::create(&config.path).map_err(|e| {
+            ApiError::Config(format!(
+                "Failed to open persistent cache at {}: {}",
+                config.path.display(),
+                e
+            ))
+        })?;
+
+        migrate(&db)
+            .map_err(|e| ApiError::Config(format!("Failed to prepare persistent cache: {}", e)))?;
+
+        Ok(Self {
+            db: Arc::new(db),
+            ttl: Duration::from_secs(config.ttl_secs),
+            stale_ttl: Duration::from_secs(config.stale_secs),
+        })
+    }
+
+    /// Reads `key` on the blocking thread pool, like writes, so a slow disk never stalls
+    /// the worker thread serving the request.
+    pub async fn get<V: DeserializeOwned>(&self, key: &str) -> Lookup<V> {
+        let db = self.db.clone();
+        let key = key.to_string();
+        let raw = tokio::task::spawn_blocking(move || read(&db, &key)).await;
+        let Ok(Ok(Some(raw))) = raw else {
+            return Lookup::Miss;
+        };
+        let Ok(entry) = serde_json::from_slice::<StoredEntry<V>>(&raw) else {
+            return Lookup::Miss;
+        };
+        if entry.version != SCHEMA_VERSION {
+            return Lookup::Miss;
+        }
+
+        let age = Duration::from_secs(unix_now().saturating_sub(entry.stored_at));
+        if let Some(ttl) = entry.ttl_secs {
+            return if age < Duration::from_secs(ttl) {
+                Lookup::Fresh(entry.value)
+            } else {
+                Lookup::Miss
+            };
+        }
+        if age < self.ttl {
+            Lookup::Fresh(entry.value)
+        } else if age < self.ttl + self.stale_ttl {
+            Lookup::Stale(entry.value)
+        } else {
+            Lookup::Miss
+        }
+    }
+
+    pub async fn insert<V: Serialize>(&self, key: &str, value: &V) {
+        self.write(key, value, None).await;
+    }
+
+    /// Inserts an entry that expires after `ttl` instead of the configured time to live,
+    /// and is not served stale after that.
+    pub async fn insert_with_ttl<V: Serialize>(&self, key: &str, value: &V, ttl: Duration) {
+        self.write(key, value, Some(ttl.as_secs())).await;
+    }
+
+    async fn write<V: Serialize>(&self, key: &str, value: &V, ttl_secs: Option<u64>) {
+        let Ok(bytes) = serde_json::to_vec(&StoredEntry {
+            version: SCHEMA_VERSION,
+            stored_at: unix_now(),
+            ttl_secs,
+            value,
+        }) else {
+            return;
+        };
+
+        let db = self.db.clone();
+        let key = key.to_string();
+        let _ = tokio::task::spawn_blocking(move || -> Result<(), redb::Error> {
+            let txn = db.begin_write()?;
+            txn.open_table(ENTRIES)?
+                .insert(key.as_str(), bytes.as_slice())?;
+            txn.commit()?;
+            Ok(())
+        })
+        .await;
+    }
+
+    /// Removes every entry whose key starts with `prefix`.
+    pub async fn remove_prefix(&self, prefix: &str) {
+        let db = self.db.clone();
+        let prefix = prefix.to_string();
+        let _ = tokio::task::spawn_blocking(move || -> Result<(), redb::Error> {
+            let txn = db.begin_write()?;
+            {
+                let mut table = txn.open_table(ENTRIES)?;
+                let keys = table
+                    .range(prefix.as_str()..)?
+                    .map_while(|entry| {
+                        let key = entry.ok()?.0.value().to_string();
+                        key.starts_with(&prefix).then_some(key)
+                    })
+                    .collect::<Vec<_>>();
+                for key in keys {
+                    table.remove(key.as_str())?;
+                }
+            }
+            txn.commit()?;
+            Ok(())
+        })
+        .await;
+    }
+
+    /// Whether the database can currently be read, checked on the blocking thread pool
+    /// like every other read.
+    pub async fn check(&self) -> Result<(), String> {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || -> Result<(), String> {
+            let txn = db.begin_read().map_err(|e| e.to_string())?;
+            txn.open_table(ENTRIES).map_err(|e| e.to_string())?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+}
+
+fn read(db: &Database, key: &str) -> Result<Option<Vec<u8>>, redb::Error> {
+    let txn = db.begin_read()?;
+    let table = txn.open_table(ENTRIES)?;
+    Ok(table.get(key)?.map(|raw| raw.value().to_vec()))
+}
+
+/// Drops all entries written under a different [`SCHEMA_VERSION`].
+fn migrate(db: &Database) -> Result<(), redb::Error> {
+    let txn = db.begin_write()?;
+    {
+        let mut meta = txn.open_table(META)?;
+        let stored = meta.get(SCHEMA_VERSION_KEY)?.map(|v| v.value());
+        if stored != Some(SCHEMA_VERSION) {
+            txn.delete_table(ENTRIES)?;
+            meta.insert(SCHEMA_VERSION_KEY, SCHEMA_VERSION)?;
+        }
+    }
+    // make sure the table exists so readers never race its creation
+    txn.open_table(ENTRIES)?;
+    txn.commit()?;
+    Ok(())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
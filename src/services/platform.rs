@@ -0,0 +1,62 @@
+use std::fmt;
+
+use serde::Deserialize;
+
+/// Stores whose ids SteamGridDB can map to its own game ids.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    Steam,
+    Gog,
+    #[serde(alias = "egs")]
+    Epic,
+    Origin,
+    #[serde(alias = "uplay")]
+    Ubisoft,
+    #[serde(alias = "bnet")]
+    Battlenet,
+}
+
+impl Platform {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Platform::Steam => "steam",
+            Platform::Gog => "gog",
+            Platform::Epic => "epic",
+            Platform::Origin => "origin",
+            Platform::Ubisoft => "ubisoft",
+            Platform::Battlenet => "battlenet",
+        }
+    }
+
+    /// The platform's name in SteamGridDB's routes.
+    pub fn upstream_name(&self) -> &'static str {
+        match self {
+            Platform::Steam => "steam",
+            Platform::Gog => "gog",
+            Platform::Epic => "egs",
+            Platform::Origin => "origin",
+            Platform::Ubisoft => "uplay",
+            Platform::Battlenet => "bnet",
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Whether `id` can be a store id: Steam and GOG use numbers, the Epic store hex strings.
+///
+/// Ids made only of dots are rejected, as `.` and `..` would be resolved as path segments
+/// in the upstream URL.
+pub fn is_valid_platform_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && !id.chars().all(|c| c == '.')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
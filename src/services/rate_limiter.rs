@@ -0,0 +1,144 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{config::RateLimitConfig, error::ApiError, services::endpoint::Endpoint};
+
+/// Longest `Retry-After` honoured; SteamGridDB asking for more is treated as this.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(60 * 60);
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+    paused_until: Option<Instant>,
+}
+
+/// Token bucket limiting the rate of outbound SteamGridDB requests.
+///
+/// Callers that find the bucket empty wait for the next token; once `max_queued` callers
+/// are waiting, further ones fail with [`ApiError::Throttled`]. A `Retry-After` from
+/// SteamGridDB pauses the bucket for everyone.
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    max_queued: usize,
+    bucket: Mutex<Bucket>,
+    queued: AtomicUsize,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: f64, burst: u32, max_queued: usize) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            requests_per_second,
+            burst,
+            max_queued,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                refilled_at: Instant::now(),
+                paused_until: None,
+            }),
+            queued: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn from_config(config: &RateLimitConfig) -> Self {
+        Self::new(config.requests_per_second, config.burst, config.max_queued)
+    }
+
+    /// Waits until a request may be sent.
+    pub async fn acquire(
+        &self,
+        endpoint: Endpoint,
+        game_id: Option<usize>,
+    ) -> Result<(), ApiError> {
+        let Some(mut wait) = self.try_take() else {
+            return Ok(());
+        };
+
+        if self.queued.fetch_add(1, Ordering::SeqCst) >= self.max_queued {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return Err(ApiError::Throttled { endpoint, game_id });
+        }
+        let _slot = QueueSlot(&self.queued);
+
+        loop {
+            tokio::time::sleep(wait).await;
+            match self.try_take() {
+                Some(next) => wait = next,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    /// Holds back all requests for `duration`, e.g. as asked by a `Retry-After` header.
+    pub fn pause(&self, duration: Duration) {
+        let now = Instant::now();
+        let until = now
+            .checked_add(duration)
+            .unwrap_or_else(|| now + MAX_RETRY_AFTER);
+        let mut bucket = self.bucket.lock().expect("rate limiter lock poisoned");
+        if bucket.paused_until.is_none_or(|current| current < until) {
+            bucket.paused_until = Some(until);
+        }
+    }
+
+    /// Number of callers currently waiting for a token.
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    /// Takes a token, or returns how long to wait before trying again.
+    fn try_take(&self) -> Option<Duration> {
+        let now = Instant::now();
+        let mut bucket = self.bucket.lock().expect("rate limiter lock poisoned");
+
+        if let Some(until) = bucket.paused_until {
+            if now < until {
+                return Some(until - now);
+            }
+            bucket.paused_until = None;
+        }
+
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.requests_per_second).min(self.burst);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            None
+        } else {
+            let wait =
+                Duration::try_from_secs_f64((1.0 - bucket.tokens) / self.requests_per_second)
+                    .unwrap_or(MAX_RETRY_AFTER);
+            Some(wait.min(MAX_RETRY_AFTER))
+        }
+    }
+}
+
+/// Frees a queue slot when its waiter is done or dropped.
+struct QueueSlot<'a>(&'a AtomicUsize);
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Parses a `Retry-After` header value, given either in seconds or as an HTTP date,
+/// capped at [`MAX_RETRY_AFTER`].
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    let delay = match value.parse::<u64>() {
+        Ok(secs) => Duration::from_secs(secs),
+        Err(_) => {
+            let at = httpdate::parse_http_date(value).ok()?;
+            at.duration_since(SystemTime::now()).unwrap_or_default()
+        }
+    };
+    Some(delay.min(MAX_RETRY_AFTER))
+}
@@ -0,0 +1,97 @@
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use crate::{config::RetryConfig, error::ApiError, services::endpoint::Endpoint};
+
+/// How often and how patiently idempotent upstream GETs are retried.
+///
+/// Backoff doubles after every attempt up to `max_backoff`, with up to `jitter` of it
+/// randomly shaved off so concurrent retries spread out, but never below a `Retry-After`
+/// sent by SteamGridDB. All attempts together never exceed `deadline`.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub jitter: f64,
+    pub deadline: Duration,
+}
+
+impl RetryPolicy {
+    /// A policy making exactly one attempt.
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            jitter: 0.0,
+            deadline: Duration::MAX,
+        }
+    }
+
+    pub fn from_config(config: &RetryConfig) -> Self {
+        Self {
+            max_attempts: config.max_attempts,
+            initial_backoff: Duration::from_millis(config.initial_backoff_ms),
+            max_backoff: Duration::from_millis(config.max_backoff_ms),
+            jitter: config.jitter,
+            deadline: Duration::from_millis(config.deadline_ms),
+        }
+    }
+
+    /// Delay before retry number `retry` (starting at 1).
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_backoff);
+        exponential.mul_f64(1.0 - self.jitter * fastrand::f64())
+    }
+
+    /// Runs `attempt` until it succeeds, fails with a non-retryable error, runs out of
+    /// attempts or hits the deadline. The last error is returned on failure.
+    pub async fn run<T, F, Fut>(
+        &self,
+        endpoint: Endpoint,
+        game_id: Option<usize>,
+        mut attempt: F,
+    ) -> Result<T, ApiError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        let deadline = Instant::now().checked_add(self.deadline);
+        let mut retry = 0;
+
+        loop {
+            let remaining = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+            let result = match remaining {
+                Some(remaining) => tokio::time::timeout(remaining, attempt())
+                    .await
+                    .unwrap_or(Err(ApiError::Timeout { endpoint, game_id })),
+                None => attempt().await,
+            };
+
+            let err = match result {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+
+            retry += 1;
+            if retry >= self.max_attempts || !err.is_retryable() {
+                return Err(err);
+            }
+
+            let backoff = self
+                .backoff(retry)
+                .max(err.retry_after().unwrap_or_default());
+            let resume_at = Instant::now().checked_add(backoff);
+            if deadline.is_some_and(|d| resume_at.is_none_or(|at| at >= d)) {
+                return Err(err);
+            }
+            tokio::time::sleep(backoff).await;
+        }
+    }
+}
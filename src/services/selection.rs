@@ -0,0 +1,117 @@
+use std::{cmp::Reverse, fmt};
+
+use crate::config::{EnrichmentConfig, Ranking};
+use crate::model::Asset;
+use crate::services::asset_filter::AssetKind;
+
+/// How enrichment picks one asset of a game out of the candidates SteamGridDB lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    /// Number of assets considered, from the first one SteamGridDB lists on.
+    pub candidates: u32,
+    pub rank_by: Ranking,
+    /// Preferred styles, best first.
+    pub styles: Vec<String>,
+    /// Preferred dimensions such as `1920x620`, best first.
+    pub dimensions: Vec<String>,
+    pub language: Option<String>,
+    /// Only look at animated assets if a game has no static one.
+    pub prefer_static: bool,
+}
+
+impl Default for Selection {
+    fn default() -> Self {
+        Self::from_config(&EnrichmentConfig::default(), AssetKind::Logo)
+    }
+}
+
+impl Selection {
+    /// The configured selection for assets of `kind`.
+    pub fn from_config(config: &EnrichmentConfig, kind: AssetKind) -> Self {
+        let (styles, dimensions) = match kind {
+            AssetKind::Logo => (config.logo_styles.clone(), Vec::new()),
+            AssetKind::Hero => (config.hero_styles.clone(), config.hero_dimensions.clone()),
+            AssetKind::Grid => (config.grid_styles.clone(), config.grid_dimensions.clone()),
+            AssetKind::Icon => (Vec::new(), Vec::new()),
+        };
+        Self {
+            candidates: config.candidates,
+            rank_by: config.rank_by,
+            styles,
+            dimensions,
+            language: config.language.clone(),
+            prefer_static: config.prefer_static,
+        }
+    }
+
+    /// Picks the best of `candidates`, given in SteamGridDB's order.
+    ///
+    /// Preferred styles come first, then preferred dimensions and language. Between
+    /// assets matching them equally well `rank_by` decides, and SteamGridDB's order
+    /// after that.
+    pub fn pick(&self, candidates: impl IntoIterator<Item = Asset>) -> Option<Asset> {
+        candidates
+            .into_iter()
+            .enumerate()
+            .min_by_key(|(position, asset)| {
+                let dimensions = format!("{}x{}", asset.width, asset.height);
+                (
+                    preference(&self.styles, &asset.style),
+                    preference(&self.dimensions, &dimensions),
+                    self.language
+                        .as_ref()
+                        .is_some_and(|language| *language != asset.language),
+                    Reverse(self.rank(asset)),
+                    *position,
+                )
+            })
+            .map(|(_, asset)| asset)
+    }
+
+    fn rank(&self, asset: &Asset) -> u32 {
+        match self.rank_by {
+            Ranking::Score => asset.score,
+            Ranking::Upvotes => asset.upvotes,
+            Ranking::First => 0,
+        }
+    }
+}
+
+impl fmt::Display for Selection {
+    /// The settings as a query string, used to tell cached picks apart.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "candidates={}&rank_by={}",
+            self.candidates,
+            self.rank_by.as_str()
+        )?;
+        if !self.styles.is_empty() {
+            write!(f, "&prefer_styles={}", self.styles.join(","))?;
+        }
+        if !self.dimensions.is_empty() {
+            write!(f, "&prefer_dimensions={}", self.dimensions.join(","))?;
+        }
+        if let Some(language) = &self.language {
+            write!(f, "&prefer_language={}", language)?;
+        }
+        if self.prefer_static {
+            f.write_str("&prefer_static=true")?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `language` looks like the two or three letter codes SteamGridDB tags assets
+/// with, e.g. `en`.
+pub fn is_valid_language(language: &str) -> bool {
+    (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_lowercase())
+}
+
+/// Position of `value` in `preferred`, or past its end if it is not preferred.
+fn preference(preferred: &[String], value: &str) -> usize {
+    preferred
+        .iter()
+        .position(|p| p == value)
+        .unwrap_or(preferred.len())
+}
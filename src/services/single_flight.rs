@@ -0,0 +1,115 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use futures::future::{BoxFuture, FutureExt, Shared};
+use tracing::Instrument;
+
+use crate::{error::ApiError, services::endpoint::Endpoint};
+
+/// Identifies an upstream request for deduplication.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestKey {
+    pub endpoint: Endpoint,
+    pub game_id: Option<usize>,
+    pub params: String,
+}
+
+impl RequestKey {
+    pub fn new(endpoint: Endpoint, game_id: Option<usize>, params: impl Into<String>) -> Self {
+        Self {
+            endpoint,
+            game_id,
+            params: params.into(),
+        }
+    }
+}
+
+impl fmt::Display for RequestKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.game_id {
+            Some(game_id) => write!(f, "{}:{}:{}", self.endpoint, game_id, self.params),
+            None => write!(f, "{}::{}", self.endpoint, self.params),
+        }
+    }
+}
+
+type SharedFetch<V> = Shared<BoxFuture<'static, Result<V, ApiError>>>;
+type Flights<V> = Arc<Mutex<HashMap<RequestKey, SharedFetch<V>>>>;
+
+/// Joins concurrent callers asking for the same [`RequestKey`] onto a single fetch.
+///
+/// The first caller's fetch runs on its own task and is shared with everyone who arrives
+/// while it is still running. It finishes even if every caller goes away, so it never
+/// holds on to a rate limiter slot or a circuit breaker probe; once it resolves the key
+/// is released, so later calls fetch again.
+pub struct SingleFlight<V> {
+    in_flight: Flights<V>,
+}
+
+impl<V> Default for SingleFlight<V> {
+    fn default() -> Self {
+        Self {
+            in_flight: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl<V: Clone + Send + Sync + 'static> SingleFlight<V> {
+    pub async fn run<F>(&self, key: RequestKey, fetch: F) -> Result<V, ApiError>
+    where
+        F: Future<Output = Result<V, ApiError>> + Send + 'static,
+    {
+        let shared = self
+            .in_flight
+            .lock()
+            .expect("single flight lock poisoned")
+            .entry(key.clone())
+            .or_insert_with(|| self.spawn(key, fetch))
+            .clone();
+
+        shared.await
+    }
+
+    /// Runs `fetch` on its own task, which releases `key` once it resolves. Callers hold
+    /// the map's lock while spawning, so the key is always in place before it is released.
+    fn spawn<F>(&self, key: RequestKey, fetch: F) -> SharedFetch<V>
+    where
+        F: Future<Output = Result<V, ApiError>> + Send + 'static,
+    {
+        let in_flight = self.in_flight.clone();
+        let task = tokio::spawn(
+            async move {
+                let result = fetch.await;
+                in_flight
+                    .lock()
+                    .expect("single flight lock poisoned")
+                    .remove(&key);
+                result
+            }
+            .in_current_span(),
+        );
+
+        async move {
+            task.await
+                .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()))
+        }
+        .boxed()
+        .shared()
+    }
+
+    /// Number of distinct requests currently in flight.
+    pub fn len(&self) -> usize {
+        self.in_flight
+            .lock()
+            .expect("single flight lock poisoned")
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -1,107 +1,861 @@
-use std::error::Error;
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use reqwest::Client as ReqwestClient;
+use reqwest::{header::RETRY_AFTER, Client as ReqwestClient, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use steamgriddb_api::{response::Response as SgdbResponse, search::SearchResult};
+use tracing::{field::Empty, Instrument};
 
-use crate::steamgriddb_models;
+use crate::error::ApiError;
+use crate::metrics::{upstream_status, Metrics};
+use crate::model::Asset;
+use crate::services::{
+    asset_filter::AssetFilter,
+    cache::{TtlCache, UpstreamCache},
+    circuit_breaker::CircuitBreaker,
+    endpoint::Endpoint,
+    persistent_cache::{Lookup, PersistentCache},
+    platform::Platform,
+    rate_limiter::{parse_retry_after, RateLimiter},
+    retry::RetryPolicy,
+    selection::Selection,
+    single_flight::{RequestKey, SingleFlight},
+};
+use crate::steamgriddb_models::{
+    AssetPage, GameData, GameResponse, GridResponseData, GridsResponse, HeroResponseData,
+    HeroesResponse, IconResponseData, IconsResponse, Listing, LogoResposeData, LogosResponse,
+};
 
+/// Largest page size requested from SteamGridDB when listing assets.
+pub const UPSTREAM_PAGE_LIMIT: u32 = 50;
+
+/// Search term used by [`SteamgriddbService::probe`].
+const PROBE_QUERY: &str = "steam";
+
+/// Upstream requests currently being fetched, shared by all clones of a service.
+#[derive(Default)]
+struct InFlightRequests {
+    search: SingleFlight<Vec<SearchResult>>,
+    games: SingleFlight<GameData>,
+    grids: SingleFlight<Listing<GridResponseData>>,
+    logos: SingleFlight<Option<String>>,
+    heroes: SingleFlight<Option<String>>,
+    grid_picks: SingleFlight<Option<String>>,
+    icons: SingleFlight<Option<String>>,
+    hero_lists: SingleFlight<Listing<HeroResponseData>>,
+    logo_lists: SingleFlight<Listing<LogoResposeData>>,
+    icon_lists: SingleFlight<Listing<IconResponseData>>,
+}
+
+type ListingFlights<T> = fn(&InFlightRequests) -> &SingleFlight<Listing<T>>;
+type ListingMemory<T> = fn(&UpstreamCache) -> &TtlCache<(usize, String), Listing<T>>;
+type PickMemory = fn(&UpstreamCache) -> &TtlCache<(usize, String), Option<String>>;
+
+/// Where pages of one asset kind are deduplicated and cached in memory.
+struct ListingSlots<T> {
+    in_flight: ListingFlights<T>,
+    memory: ListingMemory<T>,
+}
+
+impl<T> Clone for ListingSlots<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ListingSlots<T> {}
+
+/// Where the asset picked for enrichment is deduplicated and, for logos, heroes and
+/// icons, remembered.
+#[derive(Clone, Copy)]
+struct PickSlots {
+    in_flight: fn(&InFlightRequests) -> &SingleFlight<Option<String>>,
+    memory: Option<PickMemory>,
+}
+
+#[derive(Clone)]
 pub struct SteamgriddbService {
-    sg_client: Arc<steamgriddb_api::client::Client>,
     req_client: Arc<ReqwestClient>,
     base_url: String,
+    cache: Option<Arc<UpstreamCache>>,
+    persistent: Option<Arc<PersistentCache>>,
+    in_flight: Arc<InFlightRequests>,
+    retry: RetryPolicy,
+    breaker: Option<Arc<CircuitBreaker>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    metrics: Option<Arc<Metrics>>,
 }
 
 impl SteamgriddbService {
-    pub fn new(
-        sg_client: Arc<steamgriddb_api::client::Client>,
-        req_client: Arc<ReqwestClient>,
-        base_url: String,
-    ) -> Self {
+    pub fn new(req_client: Arc<ReqwestClient>, base_url: String) -> Self {
         Self {
-            sg_client,
             req_client,
             base_url,
+            cache: None,
+            persistent: None,
+            in_flight: Arc::default(),
+            retry: RetryPolicy::none(),
+            breaker: None,
+            rate_limiter: None,
+            metrics: None,
+        }
+    }
+
+    /// Serves repeated lookups from `cache` instead of asking SteamGridDB again.
+    pub fn with_cache(mut self, cache: Arc<UpstreamCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Keeps search results and grid lists in `store` across restarts, serving stale
+    /// entries while they are refreshed in the background.
+    pub fn with_persistent_cache(mut self, store: Arc<PersistentCache>) -> Self {
+        self.persistent = Some(store);
+        self
+    }
+
+    /// Retries failed upstream requests according to `policy`; by default every request
+    /// is attempted once.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Stops sending requests while `breaker` considers SteamGridDB down; they fail
+    /// with [`ApiError::CircuitOpen`] instead.
+    pub fn with_circuit_breaker(mut self, breaker: Arc<CircuitBreaker>) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// Sends requests no faster than `limiter` allows. Share one limiter between all
+    /// services talking to the same SteamGridDB account.
+    pub fn with_rate_limiter(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Records count, duration and outcome of every upstream request in `metrics`.
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn persistent_cache(&self) -> Option<&Arc<PersistentCache>> {
+        self.persistent.as_ref()
+    }
+
+    pub fn circuit_breaker(&self) -> Option<&Arc<CircuitBreaker>> {
+        self.breaker.as_ref()
+    }
+
+    pub async fn search(&self, query: &str) -> Result<Vec<SearchResult>, ApiError> {
+        let query = query.trim().to_lowercase();
+        if let Some(results) = self.cache.as_ref().and_then(|c| c.search.get(&query)) {
+            return Ok(results);
         }
+
+        let key = RequestKey::new(Endpoint::Search, None, query.as_str());
+        match self.persisted::<Vec<SearchResult>>(&key).await {
+            Lookup::Fresh(results) => {
+                if let Some(cache) = &self.cache {
+                    cache.search.insert(query, results.clone());
+                }
+                Ok(results)
+            }
+            Lookup::Stale(results) => {
+                let this = self.clone();
+                tokio::spawn(
+                    async move {
+                        if let Err(err) = this.fetch_search(key, query).await {
+                            tracing::warn!(error = %err, "refreshing stale search results failed");
+                        }
+                    }
+                    .in_current_span(),
+                );
+                Ok(results)
+            }
+            Lookup::Miss => self.fetch_search(key, query).await,
+        }
+    }
+
+    /// Details of the game with SteamGridDB id `game_id`.
+    pub async fn get_game_by_id(&self, game_id: usize) -> Result<GameData, ApiError> {
+        let path = format!("id/{}", game_id);
+        self.get_game(RequestKey::new(Endpoint::Games, Some(game_id), path))
+            .await
     }
 
+    /// Looks a game up by its id on another store, e.g. a Steam app id.
+    pub async fn get_game_by_platform_id(
+        &self,
+        platform: Platform,
+        id: &str,
+    ) -> Result<GameData, ApiError> {
+        let path = format!("{}/{}", platform.upstream_name(), id);
+        self.get_game(RequestKey::new(Endpoint::Games, None, path))
+            .await
+    }
+
+    /// Lists the grids of `game_id` matching `filter`, `limit` of them from `offset` on.
     pub async fn fetch_assets_by_game_id(
         &self,
         game_id: usize,
-    ) -> Result<Vec<steamgriddb_api::images::Image>, Box<dyn Error>> {
-        self.sg_client
-            .get_images_for_id(game_id, &steamgriddb_api::QueryType::Grid(None))
+        filter: &AssetFilter,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Listing<GridResponseData>, ApiError> {
+        let slots = ListingSlots {
+            in_flight: |f| &f.grids,
+            memory: |c| &c.grids,
+        };
+        self.fetch_window::<GridsResponse>(Endpoint::Grids, game_id, filter, offset, limit, slots)
+            .await
+    }
+
+    /// Lists the heroes of `game_id` matching `filter`, `limit` of them from `offset` on.
+    pub async fn fetch_heroes_by_game_id(
+        &self,
+        game_id: usize,
+        filter: &AssetFilter,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Listing<HeroResponseData>, ApiError> {
+        let slots = ListingSlots {
+            in_flight: |f| &f.hero_lists,
+            memory: |c| &c.hero_lists,
+        };
+        self.fetch_window::<HeroesResponse>(Endpoint::Heroes, game_id, filter, offset, limit, slots)
             .await
     }
 
-    pub async fn get_first_logo_by_game_id(
+    /// Lists the logos of `game_id` matching `filter`, `limit` of them from `offset` on.
+    pub async fn fetch_logos_by_game_id(
         &self,
         game_id: usize,
-    ) -> Result<Option<String>, Box<dyn Error>> {
-        let logos_url = format!("{}/logos/game/{}", self.base_url, game_id);
+        filter: &AssetFilter,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Listing<LogoResposeData>, ApiError> {
+        let slots = ListingSlots {
+            in_flight: |f| &f.logo_lists,
+            memory: |c| &c.logo_lists,
+        };
+        self.fetch_window::<LogosResponse>(Endpoint::Logos, game_id, filter, offset, limit, slots)
+            .await
+    }
 
-        let logos_resp = self
-            .req_client
-            .get(logos_url)
-            .query(&[("limit", 1)])
-            .send()
+    /// Lists the icons of `game_id` matching `filter`, `limit` of them from `offset` on.
+    pub async fn fetch_icons_by_game_id(
+        &self,
+        game_id: usize,
+        filter: &AssetFilter,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Listing<IconResponseData>, ApiError> {
+        let slots = ListingSlots {
+            in_flight: |f| &f.icon_lists,
+            memory: |c| &c.icon_lists,
+        };
+        self.fetch_window::<IconsResponse>(Endpoint::Icons, game_id, filter, offset, limit, slots)
+            .await
+    }
+
+    /// The url of the logo `selection` picks for `game_id` among those matching `filter`.
+    pub async fn get_best_logo_by_game_id(
+        &self,
+        game_id: usize,
+        filter: &AssetFilter,
+        selection: &Selection,
+    ) -> Result<Option<String>, ApiError> {
+        let picks = PickSlots {
+            in_flight: |f| &f.logos,
+            memory: Some(|c| &c.logos),
+        };
+        let slots = ListingSlots {
+            in_flight: |f| &f.logo_lists,
+            memory: |c| &c.logo_lists,
+        };
+        self.get_best::<LogosResponse>(Endpoint::Logos, game_id, filter, selection, picks, slots)
+            .await
+    }
+
+    /// The url of the hero `selection` picks for `game_id` among those matching `filter`.
+    pub async fn get_best_hero_by_game_id(
+        &self,
+        game_id: usize,
+        filter: &AssetFilter,
+        selection: &Selection,
+    ) -> Result<Option<String>, ApiError> {
+        let picks = PickSlots {
+            in_flight: |f| &f.heroes,
+            memory: Some(|c| &c.heroes),
+        };
+        let slots = ListingSlots {
+            in_flight: |f| &f.hero_lists,
+            memory: |c| &c.hero_lists,
+        };
+        self.get_best::<HeroesResponse>(Endpoint::Heroes, game_id, filter, selection, picks, slots)
+            .await
+    }
+
+    /// The url of the grid `selection` picks for `game_id` among those matching `filter`.
+    pub async fn get_best_grid_by_game_id(
+        &self,
+        game_id: usize,
+        filter: &AssetFilter,
+        selection: &Selection,
+    ) -> Result<Option<String>, ApiError> {
+        let picks = PickSlots {
+            in_flight: |f| &f.grid_picks,
+            memory: None,
+        };
+        let slots = ListingSlots {
+            in_flight: |f| &f.grids,
+            memory: |c| &c.grids,
+        };
+        self.get_best::<GridsResponse>(Endpoint::Grids, game_id, filter, selection, picks, slots)
+            .await
+    }
+
+    /// The url of the icon `selection` picks for `game_id` among those matching `filter`.
+    pub async fn get_best_icon_by_game_id(
+        &self,
+        game_id: usize,
+        filter: &AssetFilter,
+        selection: &Selection,
+    ) -> Result<Option<String>, ApiError> {
+        let picks = PickSlots {
+            in_flight: |f| &f.icons,
+            memory: Some(|c| &c.icons),
+        };
+        let slots = ListingSlots {
+            in_flight: |f| &f.icon_lists,
+            memory: |c| &c.icon_lists,
+        };
+        self.get_best::<IconsResponse>(Endpoint::Icons, game_id, filter, selection, picks, slots)
+            .await
+    }
+
+    /// Sends a single uncached search to check that SteamGridDB answers. It passes the
+    /// rate limiter and circuit breaker like any other request, but is never retried.
+    pub async fn probe(&self) -> Result<(), ApiError> {
+        let url = steamgriddb_api::search::get_search_url(&self.base_url, PROBE_QUERY);
+        let resp: SgdbResponse<Vec<SearchResult>> = self
+            .get_json_guarded(Endpoint::Search, None, &url, &[])
             .await?;
 
-        if !logos_resp.status().is_success() {
-            return Err("Failed to fetch logo".into());
+        if !resp.success.unwrap_or(false) {
+            return Err(ApiError::Unsuccessful {
+                endpoint: Endpoint::Search,
+                game_id: None,
+            });
         }
+        Ok(())
+    }
 
-        let logos_resp_json: steamgriddb_models::LogosResponse =
-            serde_json::from_str(&logos_resp.text().await?)?;
+    /// Drops everything cached for `game_id`, in memory and on disk, so the next
+    /// lookup goes to SteamGridDB again.
+    pub async fn invalidate_game(&self, game_id: usize) {
+        if let Some(cache) = &self.cache {
+            cache.invalidate_game(game_id);
+        }
+        if let Some(store) = &self.persistent {
+            for endpoint in [
+                Endpoint::Games,
+                Endpoint::Grids,
+                Endpoint::Heroes,
+                Endpoint::Logos,
+                Endpoint::Icons,
+            ] {
+                store
+                    .remove_prefix(&format!("{}:{}:", endpoint, game_id))
+                    .await;
+            }
+        }
+    }
 
-        if !logos_resp_json.success {
-            return Err("steamgriddb API returned success=false for logos".into());
+    async fn get_best<R>(
+        &self,
+        endpoint: Endpoint,
+        game_id: usize,
+        filter: &AssetFilter,
+        selection: &Selection,
+        picks: PickSlots,
+        slots: ListingSlots<R::Item>,
+    ) -> Result<Option<String>, ApiError>
+    where
+        R: AssetPage + Send + 'static,
+        R::Item: Into<Asset> + Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
+    {
+        let params = match filter.to_string() {
+            filters if filters.is_empty() => selection.to_string(),
+            filters => format!("{}&{}", filters, selection),
+        };
+        let cache_key = (game_id, params.clone());
+        let memory = self.cache.as_deref().zip(picks.memory);
+        if let Some(url) = memory.and_then(|(cache, memory)| memory(cache).get(&cache_key)) {
+            return Ok(url);
         }
 
-        if logos_resp_json.data.is_empty() {
-            return Ok(None);
+        let key = RequestKey::new(endpoint, Some(game_id), params);
+        let this = self.clone();
+        let filter = filter.clone();
+        let selection = selection.clone();
+        (picks.in_flight)(&self.in_flight)
+            .run(key, async move {
+                let url = this
+                    .request_best::<R>(endpoint, game_id, &filter, &selection, slots)
+                    .await?;
+                if let Some((cache, memory)) = this.cache.as_deref().zip(picks.memory) {
+                    let memory = memory(cache);
+                    match url {
+                        Some(_) => memory.insert(cache_key, url.clone()),
+                        None => memory.insert_with_ttl(cache_key, None, cache.negative_ttl),
+                    }
+                }
+                Ok(url)
+            })
+            .await
+    }
+
+    /// Fetches the candidate window of `selection` and picks from it. With
+    /// `prefer_static`, animated assets are only fetched if there is no static one.
+    async fn request_best<R>(
+        &self,
+        endpoint: Endpoint,
+        game_id: usize,
+        filter: &AssetFilter,
+        selection: &Selection,
+        slots: ListingSlots<R::Item>,
+    ) -> Result<Option<String>, ApiError>
+    where
+        R: AssetPage + Send + 'static,
+        R::Item: Into<Asset> + Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
+    {
+        let mut window = filter.clone();
+        if selection.prefer_static && window.animated.is_none() {
+            window.animated = Some(false);
         }
 
-        let first = logos_resp_json
-            .data
-            .first()
-            .expect("checked non-empty above");
+        let mut candidates = self
+            .fetch_window::<R>(endpoint, game_id, &window, 0, selection.candidates, slots)
+            .await?
+            .data;
+        if candidates.is_empty() && window != *filter {
+            candidates = self
+                .fetch_window::<R>(endpoint, game_id, filter, 0, selection.candidates, slots)
+                .await?
+                .data;
+        }
 
-        Ok(Some(first.url.to_owned()))
+        Ok(selection
+            .pick(candidates.into_iter().map(Into::into))
+            .map(|asset| asset.url))
     }
 
-    pub async fn get_first_hero_by_game_id(
+    async fn fetch_search(
         &self,
+        key: RequestKey,
+        query: String,
+    ) -> Result<Vec<SearchResult>, ApiError> {
+        let this = self.clone();
+        self.in_flight
+            .search
+            .run(
+                key.clone(),
+                async move { this.request_search(key, query).await },
+            )
+            .await
+    }
+
+    /// Answers a game lookup from the persistent cache or SteamGridDB. The key's params
+    /// are the lookup's path below `/games/`.
+    async fn get_game(&self, key: RequestKey) -> Result<GameData, ApiError> {
+        match self.persisted::<GameData>(&key).await {
+            Lookup::Fresh(game) => Ok(game),
+            Lookup::Stale(game) => {
+                let this = self.clone();
+                tokio::spawn(
+                    async move {
+                        if let Err(err) = this.fetch_game(key).await {
+                            tracing::warn!(error = %err, "refreshing stale game failed");
+                        }
+                    }
+                    .in_current_span(),
+                );
+                Ok(game)
+            }
+            Lookup::Miss => self.fetch_game(key).await,
+        }
+    }
+
+    async fn fetch_game(&self, key: RequestKey) -> Result<GameData, ApiError> {
+        let this = self.clone();
+        self.in_flight
+            .games
+            .run(key.clone(), async move { this.request_game(key).await })
+            .await
+    }
+
+    /// Fetches the pages of SteamGridDB's listing that overlap `offset..offset + limit`
+    /// and cuts the window out of them. Pages are requested with the window's size, capped
+    /// at [`UPSTREAM_PAGE_LIMIT`], so an aligned window is a single upstream request.
+    async fn fetch_window<R>(
+        &self,
+        endpoint: Endpoint,
         game_id: usize,
-    ) -> Result<Option<String>, Box<dyn Error>> {
-        let heroes_url = format!("{}/heroes/game/{}", self.base_url, game_id);
+        filter: &AssetFilter,
+        offset: u32,
+        limit: u32,
+        slots: ListingSlots<R::Item>,
+    ) -> Result<Listing<R::Item>, ApiError>
+    where
+        R: AssetPage + Send + 'static,
+        R::Item: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
+    {
+        let page_size = limit.clamp(1, UPSTREAM_PAGE_LIMIT);
+        let first_page = offset / page_size;
+        let last_page = offset.saturating_add(limit.max(1) - 1) / page_size;
 
-        let heroes_resp = self
-            .req_client
-            .get(heroes_url)
-            .query(&[("limit", 1)])
-            .send()
+        let mut data = Vec::new();
+        let mut total = 0;
+        for page in first_page..=last_page {
+            let listing = self
+                .fetch_page::<R>(endpoint, game_id, filter, page, page_size, slots)
+                .await?;
+            total = listing.total;
+            let exhausted = listing.data.len() < page_size as usize;
+            data.extend(listing.data);
+            if exhausted {
+                break;
+            }
+        }
+
+        let skip = (offset - first_page * page_size) as usize;
+        Ok(Listing {
+            data: data.into_iter().skip(skip).take(limit as usize).collect(),
+            total,
+        })
+    }
+
+    /// A single upstream page, from the in-memory cache, the persistent cache or
+    /// SteamGridDB.
+    async fn fetch_page<R>(
+        &self,
+        endpoint: Endpoint,
+        game_id: usize,
+        filter: &AssetFilter,
+        page: u32,
+        limit: u32,
+        slots: ListingSlots<R::Item>,
+    ) -> Result<Listing<R::Item>, ApiError>
+    where
+        R: AssetPage + Send + 'static,
+        R::Item: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
+    {
+        let params = match filter.to_string() {
+            filters if filters.is_empty() => format!("page={}&limit={}", page, limit),
+            filters => format!("{}&page={}&limit={}", filters, page, limit),
+        };
+        let memory = self.cache.as_deref().map(|cache| (slots.memory)(cache));
+        let cache_key = (game_id, params.clone());
+        if let Some(listing) = memory.and_then(|m| m.get(&cache_key)) {
+            return Ok(listing);
+        }
+
+        let key = RequestKey::new(endpoint, Some(game_id), params);
+        let listing = match self.persisted::<Listing<R::Item>>(&key).await {
+            Lookup::Fresh(listing) => listing,
+            Lookup::Stale(listing) => {
+                let this = self.clone();
+                let filter = filter.clone();
+                tokio::spawn(
+                    async move {
+                        if let Err(err) = this
+                            .fetch_page_once::<R>(key, filter, page, limit, slots.in_flight)
+                            .await
+                        {
+                            tracing::warn!(game_id, %endpoint, error = %err, "refreshing stale assets failed");
+                        }
+                    }
+                    .in_current_span(),
+                );
+                return Ok(listing);
+            }
+            Lookup::Miss => {
+                self.fetch_page_once::<R>(key, filter.clone(), page, limit, slots.in_flight)
+                    .await?
+            }
+        };
+
+        if let Some(memory) = memory {
+            match self.negative_ttl().filter(|_| listing.data.is_empty()) {
+                Some(ttl) => memory.insert_with_ttl(cache_key, listing.clone(), ttl),
+                None => memory.insert(cache_key, listing.clone()),
+            }
+        }
+        Ok(listing)
+    }
+
+    async fn fetch_page_once<R>(
+        &self,
+        key: RequestKey,
+        filter: AssetFilter,
+        page: u32,
+        limit: u32,
+        in_flight: ListingFlights<R::Item>,
+    ) -> Result<Listing<R::Item>, ApiError>
+    where
+        R: AssetPage + Send + 'static,
+        R::Item: Serialize + Clone + Send + Sync + 'static,
+    {
+        let this = self.clone();
+        in_flight(&self.in_flight)
+            .run(key.clone(), async move {
+                this.request_page::<R>(key, &filter, page, limit).await
+            })
+            .await
+    }
+
+    /// How long an empty listing is remembered. Without the in-memory cache there is no
+    /// negative time to live configured, and empty listings are not persisted either.
+    fn negative_ttl(&self) -> Option<Duration> {
+        self.cache.as_ref().map(|cache| cache.negative_ttl)
+    }
+
+    /// Looks `key` up in the persistent cache, if one is configured.
+    async fn persisted<V: DeserializeOwned>(&self, key: &RequestKey) -> Lookup<V> {
+        match &self.persistent {
+            Some(store) => store.get(&key.to_string()).await,
+            None => Lookup::Miss,
+        }
+    }
+
+    async fn request_search(
+        &self,
+        key: RequestKey,
+        query: String,
+    ) -> Result<Vec<SearchResult>, ApiError> {
+        let search_url = steamgriddb_api::search::get_search_url(&self.base_url, &query);
+
+        let search_resp: SgdbResponse<Vec<SearchResult>> = self
+            .get_json(Endpoint::Search, None, search_url, &[])
             .await?;
 
-        if !heroes_resp.status().is_success() {
-            return Err("Failed to fetch heroes".into());
+        if !search_resp.success.unwrap_or(false) {
+            return Err(ApiError::Unsuccessful {
+                endpoint: Endpoint::Search,
+                game_id: None,
+            });
         }
 
-        let heroes_resp_json: steamgriddb_models::HeroesResponse =
-            serde_json::from_str(&heroes_resp.text().await?)?;
+        let results = search_resp.data.unwrap_or_default();
+        if let Some(store) = &self.persistent {
+            store.insert(&key.to_string(), &results).await;
+        }
+        if let Some(cache) = &self.cache {
+            cache.search.insert(query, results.clone());
+        }
+
+        Ok(results)
+    }
 
-        if !heroes_resp_json.success {
-            return Err("steamgriddb API returned success=false for heroes".into());
+    async fn request_game(&self, key: RequestKey) -> Result<GameData, ApiError> {
+        let url = format!("{}/games/{}", self.base_url, key.params);
+
+        let resp: GameResponse = self
+            .get_json(Endpoint::Games, key.game_id, url, &[])
+            .await?;
+
+        let game = match resp.data {
+            Some(game) if resp.success => game,
+            _ => {
+                return Err(ApiError::Unsuccessful {
+                    endpoint: Endpoint::Games,
+                    game_id: key.game_id,
+                })
+            }
+        };
+        if let Some(store) = &self.persistent {
+            store.insert(&key.to_string(), &game).await;
         }
 
-        if heroes_resp_json.data.is_empty() {
-            return Ok(None);
+        Ok(game)
+    }
+
+    async fn request_page<R>(
+        &self,
+        key: RequestKey,
+        filter: &AssetFilter,
+        page: u32,
+        limit: u32,
+    ) -> Result<Listing<R::Item>, ApiError>
+    where
+        R: AssetPage,
+        R::Item: Serialize,
+    {
+        let endpoint = key.endpoint;
+        let game_id = key.game_id;
+        let url = format!(
+            "{}/{}/game/{}",
+            self.base_url,
+            endpoint,
+            game_id.unwrap_or_default()
+        );
+
+        let mut query = filter.query();
+        query.push(("page", page.to_string()));
+        query.push(("limit", limit.to_string()));
+        let resp: R = self.get_json(endpoint, game_id, url, &query).await?;
+
+        if !resp.success() {
+            return Err(ApiError::Unsuccessful { endpoint, game_id });
         }
 
-        let first = heroes_resp_json
-            .data
-            .first()
-            .expect("checked non-empty above");
+        let listing = resp.into_listing();
+        if let Some(store) = &self.persistent {
+            if !listing.data.is_empty() {
+                store.insert(&key.to_string(), &listing).await;
+            } else if let Some(ttl) = self.negative_ttl() {
+                store.insert_with_ttl(&key.to_string(), &listing, ttl).await;
+            }
+        }
+
+        Ok(listing)
+    }
+
+    /// Performs a GET against SteamGridDB and decodes the JSON body, retrying transient
+    /// failures and mapping every failure to an [`ApiError`] tagged with `endpoint` and
+    /// `game_id`.
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        endpoint: Endpoint,
+        game_id: Option<usize>,
+        url: String,
+        query: &[(&str, String)],
+    ) -> Result<T, ApiError> {
+        self.retry
+            .run(endpoint, game_id, || {
+                let span = tracing::info_span!("upstream", %endpoint, game_id, status = Empty);
+                self.get_json_guarded(endpoint, game_id, &url, query)
+                    .instrument(span)
+            })
+            .await
+    }
+
+    /// A single attempt, short-circuited while the circuit breaker is open and held back
+    /// by the rate limiter. Its outcome is reported back to both and to the metrics.
+    async fn get_json_guarded<T: DeserializeOwned>(
+        &self,
+        endpoint: Endpoint,
+        game_id: Option<usize>,
+        url: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, ApiError> {
+        if let Some(breaker) = &self.breaker {
+            if !breaker.allow_request() {
+                let err = ApiError::CircuitOpen { endpoint, game_id };
+                return self.observed(endpoint, Err(err), None);
+            }
+        }
+        if let Some(limiter) = &self.rate_limiter {
+            if let Err(err) = limiter.acquire(endpoint, game_id).await {
+                return self.observed(endpoint, Err(err), None);
+            }
+        }
+
+        let started = Instant::now();
+        let result = self.get_json_once(endpoint, game_id, url, query).await;
+        let elapsed = started.elapsed();
+
+        if let (Some(limiter), Some(retry_after)) = (
+            &self.rate_limiter,
+            result.as_ref().err().and_then(ApiError::retry_after),
+        ) {
+            limiter.pause(retry_after);
+        }
+        if let Some(breaker) = &self.breaker {
+            match &result {
+                Err(err) if err.is_outage() => breaker.record_failure(),
+                _ => breaker.record_success(),
+            }
+        }
+        self.observed(endpoint, result, Some(elapsed))
+    }
+
+    /// Records an upstream attempt in the current span, the logs and the metrics;
+    /// `elapsed` is `None` if nothing was sent.
+    fn observed<T>(
+        &self,
+        endpoint: Endpoint,
+        result: Result<T, ApiError>,
+        elapsed: Option<Duration>,
+    ) -> Result<T, ApiError> {
+        let status = upstream_status(&result);
+        let status = status.as_ref();
+        let duration_ms = elapsed.map(|d| d.as_millis() as u64);
+        tracing::Span::current().record("status", status);
+        match &result {
+            Ok(_) => tracing::debug!(status, duration_ms, "upstream request completed"),
+            Err(err) => {
+                tracing::warn!(status, duration_ms, error = %err, "upstream request failed")
+            }
+        }
+
+        if let Some(metrics) = &self.metrics {
+            metrics.observe_upstream(endpoint, &result, elapsed.map(|d| d.as_secs_f64()));
+        }
+        result
+    }
+
+    async fn get_json_once<T: DeserializeOwned>(
+        &self,
+        endpoint: Endpoint,
+        game_id: Option<usize>,
+        url: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, ApiError> {
+        let resp = self
+            .req_client
+            .get(url)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| ApiError::from_reqwest(endpoint, game_id, e))?;
+
+        if resp.status() == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = resp
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after);
+            return Err(ApiError::UpstreamRateLimited {
+                endpoint,
+                game_id,
+                retry_after,
+            });
+        }
+
+        if !resp.status().is_success() {
+            return Err(ApiError::UpstreamStatus {
+                endpoint,
+                game_id,
+                status: resp.status().as_u16(),
+            });
+        }
+
+        let body = resp
+            .text()
+            .await
+            .map_err(|e| ApiError::from_reqwest(endpoint, game_id, e))?;
 
-        Ok(Some(first.url.to_owned()))
+        serde_json::from_str(&body).map_err(|e| ApiError::Decode {
+            endpoint,
+            game_id,
+            message: e.to_string(),
+        })
     }
 }
@@ -0,0 +1,246 @@
+mod common;
+
+use actix_web::{http::StatusCode, test, web, App};
+use common::config;
+use cosy_gameapi::config::ContentRule;
+use cosy_gameapi::routes::{self, get_assets_by_id};
+use cosy_gameapi::{Config, GlobalState};
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use serde_json::Value;
+
+const HERO: &str = r#"{"id":1,"url":"https://example.com/hero.png","thumb":"https://example.com/hero_thumb.png","score":0,"style":"blurred","width":1920,"height":620,"nsfw":false,"humor":false,"mime":"image/png","language":"en","lock":false,"epilepsy":false,"upvotes":4,"downvotes":1,"author":{"name":"someone","steam64":"76561198000000000","avatar":null}}"#;
+
+#[actix_web::test]
+async fn type_selects_the_listed_asset_kind() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET)
+            .path("/heroes/game/3")
+            .query_param("styles", "blurred");
+        then.status(200).body(format!(
+            r#"{{"success":true,"page":0,"total":1,"limit":50,"data":[{}]}}"#,
+            HERO
+        ));
+    });
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &config(&server)).unwrap(),
+            ))
+            .app_data(routes::query_config())
+            .service(get_assets_by_id),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/assets/3?type=hero&style=blurred")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(
+        body["data"]["assets"][0]["url"],
+        "https://example.com/hero.png"
+    );
+    assert_eq!(body["data"]["assets"][0]["width"], 1920);
+    assert_eq!(body["data"]["total"], 1);
+    assert_eq!(body["data"]["is_final"], true);
+    m.assert_hits(1);
+}
+
+#[actix_web::test]
+async fn upstream_total_decides_is_final() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET)
+            .path("/heroes/game/3")
+            .query_param("page", "0")
+            .query_param("limit", "1");
+        then.status(200).body(format!(
+            r#"{{"success":true,"page":0,"total":40,"limit":1,"data":[{}]}}"#,
+            HERO
+        ));
+    });
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &config(&server)).unwrap(),
+            ))
+            .service(get_assets_by_id),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/assets/3?type=hero&limit=1")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"]["total"], 40);
+    assert_eq!(body["data"]["is_final"], false);
+    m.assert_hits(1);
+}
+
+#[actix_web::test]
+async fn largest_offset_does_not_overflow() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET)
+            .path("/heroes/game/3")
+            .query_param("page", "4294967295")
+            .query_param("limit", "1");
+        then.status(200)
+            .body(r#"{"success":true,"page":4294967295,"total":40,"limit":1,"data":[]}"#);
+    });
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &config(&server)).unwrap(),
+            ))
+            .service(get_assets_by_id),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/assets/3?type=hero&offset=4294967295&limit=1")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"]["assets"], serde_json::json!([]));
+    assert_eq!(body["data"]["is_final"], true);
+    m.assert_hits(1);
+}
+
+#[actix_web::test]
+async fn assets_carry_thumbnail_votes_and_author() {
+    let server = MockServer::start();
+    let _m = server.mock(|when, then| {
+        when.method(GET).path("/heroes/game/3");
+        then.status(200).body(format!(
+            r#"{{"success":true,"page":0,"total":1,"limit":50,"data":[{}]}}"#,
+            HERO
+        ));
+    });
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &config(&server)).unwrap(),
+            ))
+            .service(get_assets_by_id),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/assets/3?type=hero")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    let asset = &body["data"]["assets"][0];
+    assert_eq!(asset["id"], 1);
+    assert_eq!(asset["thumb"], "https://example.com/hero_thumb.png");
+    assert_eq!(asset["style"], "blurred");
+    assert_eq!(asset["mime"], "image/png");
+    assert_eq!(asset["upvotes"], 4);
+    assert_eq!(asset["downvotes"], 1);
+    assert_eq!(asset["nsfw"], false);
+    assert_eq!(asset["author"]["name"], "someone");
+    assert!(asset["author"].get("avatar").is_none());
+}
+
+#[actix_web::test]
+async fn filters_are_checked_against_the_asset_kind() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &Config::default()).unwrap(),
+            ))
+            .app_data(routes::query_config())
+            .service(get_assets_by_id),
+    )
+    .await;
+
+    for uri in [
+        "/assets/3?type=poster",
+        "/assets/3?type=hero&style=white_logo",
+        "/assets/3?type=logo&dimensions=600x900",
+        "/assets/3?type=icon&mime=jpeg",
+    ] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", uri);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["error_code"], "INVALID_QUERY", "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn flagged_assets_are_left_out_unless_requested() {
+    let server = MockServer::start();
+    let safe = server.mock(|when, then| {
+        when.method(GET)
+            .path("/grids/game/3")
+            .query_param("nsfw", "false")
+            .query_param("humor", "false")
+            .query_param("epilepsy", "false");
+        then.status(200)
+            .body(r#"{"success":true,"page":0,"total":0,"limit":15,"data":[]}"#);
+    });
+    let opted_in = server.mock(|when, then| {
+        when.method(GET)
+            .path("/grids/game/4")
+            .query_param("nsfw", "any")
+            .query_param("humor", "false");
+        then.status(200)
+            .body(r#"{"success":true,"page":0,"total":0,"limit":15,"data":[]}"#);
+    });
+
+    let mut config = config(&server);
+    config.content.humor = ContentRule::Block;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(GlobalState::new("dummy", &config).unwrap()))
+            .service(get_assets_by_id),
+    )
+    .await;
+
+    for uri in ["/assets/3", "/assets/4?nsfw=any"] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK, "{}", uri);
+    }
+    safe.assert_hits(1);
+    opted_in.assert_hits(1);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/assets/4?humor=true")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error_code"], "INVALID_QUERY");
+    assert_eq!(body["message"], "humor assets are disabled on this server");
+}
@@ -0,0 +1,227 @@
+use cosy_gameapi::config::CacheConfig;
+use cosy_gameapi::services::asset_filter::AssetFilter;
+use cosy_gameapi::services::cache::{TtlCache, UpstreamCache};
+use cosy_gameapi::services::selection::Selection;
+use cosy_gameapi::SteamgriddbService;
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use reqwest::Client;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[test]
+fn entries_expire_after_ttl() {
+    let cache: TtlCache<u32, &str> = TtlCache::new(Duration::from_millis(20), 10);
+    cache.insert(1, "one");
+    assert_eq!(cache.get(&1), Some("one"));
+
+    std::thread::sleep(Duration::from_millis(40));
+    assert_eq!(cache.get(&1), None);
+
+    let stats = cache.stats();
+    assert_eq!(stats.hits, 1);
+    assert_eq!(stats.misses, 1);
+    assert_eq!(stats.entries, 0);
+}
+
+#[test]
+fn full_cache_evicts_entry_closest_to_expiry() {
+    let cache: TtlCache<u32, u32> = TtlCache::new(Duration::from_secs(60), 2);
+    cache.insert(1, 1);
+    std::thread::sleep(Duration::from_millis(2));
+    cache.insert(2, 2);
+    cache.insert(3, 3);
+
+    assert_eq!(cache.get(&1), None);
+    assert_eq!(cache.get(&2), Some(2));
+    assert_eq!(cache.get(&3), Some(3));
+    assert_eq!(cache.stats().entries, 2);
+}
+
+#[tokio::test]
+async fn cached_logo_is_fetched_once() {
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET)
+            .path("/logos/game/99")
+            .query_param("limit", "10");
+        then.status(200).body(r#"{"success":true,"page":1,"total":1,"limit":1,"data":[{"id":1,"url":"https://example.com/logo.png","thumb":"thumb","score":0,"style":"","width":1,"height":1,"nsfw":false,"humor":false,"mime":"image/png","language":"","lock":false,"epilepsy":false,"upvotes":0,"downvotes":0,"author":{"name":"","steam64":"","avatar":""}}]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let cache = Arc::new(UpstreamCache::new(&CacheConfig::default()));
+    let service =
+        SteamgriddbService::new(Arc::new(client), server.base_url()).with_cache(cache.clone());
+
+    for _ in 0..3 {
+        let res = service
+            .get_best_logo_by_game_id(99, &AssetFilter::default(), &Selection::default())
+            .await
+            .unwrap();
+        assert_eq!(res.unwrap(), "https://example.com/logo.png");
+    }
+
+    m.assert_hits(1);
+    let stats = cache.logos.stats();
+    assert_eq!(stats.hits, 2);
+    assert_eq!(stats.misses, 1);
+}
+
+#[tokio::test]
+async fn hero_logo_and_icon_pages_are_fetched_once() {
+    let server = MockServer::start();
+
+    let mocks = ["heroes", "logos", "icons"].map(|kind| {
+        server.mock(|when, then| {
+            when.method(GET)
+                .path(format!("/{}/game/5", kind))
+                .query_param("limit", "10");
+            then.status(200)
+                .body(r#"{"success":true,"page":0,"total":0,"limit":10,"data":[]}"#);
+        })
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let cache = Arc::new(UpstreamCache::new(&CacheConfig::default()));
+    let service =
+        SteamgriddbService::new(Arc::new(client), server.base_url()).with_cache(cache.clone());
+
+    let filter = AssetFilter::default();
+    for _ in 0..2 {
+        service
+            .fetch_heroes_by_game_id(5, &filter, 0, 10)
+            .await
+            .unwrap();
+        service
+            .fetch_logos_by_game_id(5, &filter, 0, 10)
+            .await
+            .unwrap();
+        service
+            .fetch_icons_by_game_id(5, &filter, 0, 10)
+            .await
+            .unwrap();
+    }
+
+    for m in &mocks {
+        m.assert_hits(1);
+    }
+    assert_eq!(cache.hero_lists.stats().hits, 1);
+    assert_eq!(cache.logo_lists.stats().hits, 1);
+    assert_eq!(cache.icon_lists.stats().hits, 1);
+
+    cache.invalidate_game(5);
+    assert_eq!(cache.hero_lists.stats().entries, 0);
+}
+
+#[tokio::test]
+async fn picked_icon_is_fetched_once() {
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/icons/game/12");
+        then.status(200)
+            .body(r#"{"success":true,"page":0,"total":0,"limit":10,"data":[]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let cache = Arc::new(UpstreamCache::new(&CacheConfig::default()));
+    let service =
+        SteamgriddbService::new(Arc::new(client), server.base_url()).with_cache(cache.clone());
+
+    for _ in 0..3 {
+        assert!(service
+            .get_best_icon_by_game_id(12, &AssetFilter::default(), &Selection::default())
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    m.assert_hits(1);
+    assert_eq!(cache.icons.stats().hits, 2);
+
+    cache.invalidate_game(12);
+    assert_eq!(cache.icons.stats().entries, 0);
+}
+
+#[tokio::test]
+async fn missing_logo_is_remembered_until_invalidated() {
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET)
+            .path("/logos/game/42")
+            .query_param("limit", "10");
+        then.status(200)
+            .body(r#"{"success":true,"page":1,"total":0,"limit":1,"data":[]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let cache = Arc::new(UpstreamCache::new(&CacheConfig::default()));
+    let service =
+        SteamgriddbService::new(Arc::new(client), server.base_url()).with_cache(cache.clone());
+
+    assert!(service
+        .get_best_logo_by_game_id(42, &AssetFilter::default(), &Selection::default())
+        .await
+        .unwrap()
+        .is_none());
+    assert!(service
+        .get_best_logo_by_game_id(42, &AssetFilter::default(), &Selection::default())
+        .await
+        .unwrap()
+        .is_none());
+    m.assert_hits(1);
+
+    service.invalidate_game(42).await;
+    assert!(service
+        .get_best_logo_by_game_id(42, &AssetFilter::default(), &Selection::default())
+        .await
+        .unwrap()
+        .is_none());
+    m.assert_hits(2);
+}
+
+#[tokio::test]
+async fn negative_ttl_is_independent_of_positive_ttl() {
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/heroes/game/3");
+        then.status(200)
+            .body(r#"{"success":true,"page":1,"total":0,"limit":1,"data":[]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let config = CacheConfig {
+        negative_ttl_secs: 0,
+        ..CacheConfig::default()
+    };
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url())
+        .with_cache(Arc::new(UpstreamCache::new(&config)));
+
+    service
+        .get_best_hero_by_game_id(3, &AssetFilter::default(), &Selection::default())
+        .await
+        .unwrap();
+    service
+        .get_best_hero_by_game_id(3, &AssetFilter::default(), &Selection::default())
+        .await
+        .unwrap();
+    m.assert_hits(2);
+}
@@ -0,0 +1,152 @@
+mod common;
+
+use actix_web::{test, web, App};
+use common::service;
+use cosy_gameapi::services::asset_filter::AssetFilter;
+use cosy_gameapi::services::circuit_breaker::{CircuitBreaker, CircuitState};
+use cosy_gameapi::services::selection::Selection;
+use cosy_gameapi::{routes::upstream_status, ApiError, Config, GlobalState};
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+#[tokio::test]
+async fn opens_after_threshold_and_fails_fast() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/logos/game/1");
+        then.status(503);
+    });
+
+    let breaker = Arc::new(CircuitBreaker::new(3, Duration::from_secs(60)));
+    let service = service(&server).with_circuit_breaker(breaker.clone());
+
+    for _ in 0..3 {
+        let res = service
+            .get_best_logo_by_game_id(1, &AssetFilter::default(), &Selection::default())
+            .await;
+        assert!(matches!(
+            res,
+            Err(ApiError::UpstreamStatus { status: 503, .. })
+        ));
+    }
+    assert_eq!(breaker.status().state, CircuitState::Open);
+
+    let res = service
+        .get_best_logo_by_game_id(1, &AssetFilter::default(), &Selection::default())
+        .await;
+    assert!(matches!(res, Err(ApiError::CircuitOpen { .. })));
+    m.assert_hits(3);
+}
+
+#[tokio::test]
+async fn successful_probe_closes_the_circuit() {
+    let server = MockServer::start();
+    let mut failing = server.mock(|when, then| {
+        when.method(GET).path("/heroes/game/1");
+        then.status(500);
+    });
+
+    let breaker = Arc::new(CircuitBreaker::new(1, Duration::from_millis(50)));
+    let service = service(&server).with_circuit_breaker(breaker.clone());
+
+    assert!(service
+        .get_best_hero_by_game_id(1, &AssetFilter::default(), &Selection::default())
+        .await
+        .is_err());
+    assert_eq!(breaker.status().state, CircuitState::Open);
+
+    failing.delete();
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/heroes/game/1");
+        then.status(200)
+            .body(r#"{"success":true,"page":1,"total":0,"limit":1,"data":[]}"#);
+    });
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(
+        service
+            .get_best_hero_by_game_id(1, &AssetFilter::default(), &Selection::default())
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(breaker.status().state, CircuitState::Closed);
+    m.assert_hits(1);
+}
+
+#[tokio::test]
+async fn failed_probe_reopens_the_circuit() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/logos/game/1");
+        then.status(502);
+    });
+
+    let breaker = Arc::new(CircuitBreaker::new(1, Duration::from_millis(50)));
+    let service = service(&server).with_circuit_breaker(breaker.clone());
+
+    assert!(service
+        .get_best_logo_by_game_id(1, &AssetFilter::default(), &Selection::default())
+        .await
+        .is_err());
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(matches!(
+        service
+            .get_best_logo_by_game_id(1, &AssetFilter::default(), &Selection::default())
+            .await,
+        Err(ApiError::UpstreamStatus { status: 502, .. })
+    ));
+
+    let status = breaker.status();
+    assert_eq!(status.state, CircuitState::Open);
+    assert_eq!(status.times_opened, 2);
+    m.assert_hits(2);
+}
+
+#[tokio::test]
+async fn client_errors_do_not_open_the_circuit() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/logos/game/1");
+        then.status(404);
+    });
+
+    let breaker = Arc::new(CircuitBreaker::new(2, Duration::from_secs(60)));
+    let service = service(&server).with_circuit_breaker(breaker.clone());
+
+    for _ in 0..3 {
+        assert!(service
+            .get_best_logo_by_game_id(1, &AssetFilter::default(), &Selection::default())
+            .await
+            .is_err());
+    }
+    assert_eq!(breaker.status().state, CircuitState::Closed);
+    m.assert_hits(3);
+}
+
+#[actix_web::test]
+async fn status_endpoint_reports_breaker_state() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &Config::default()).unwrap(),
+            ))
+            .service(upstream_status),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/status/upstream")
+            .to_request(),
+    )
+    .await;
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["success"], true);
+    assert_eq!(body["data"]["circuit_breaker"]["state"], "closed");
+    assert_eq!(body["data"]["circuit_breaker"]["consecutive_failures"], 0);
+}
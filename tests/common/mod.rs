@@ -0,0 +1,27 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)] // every test crate uses its own subset
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use cosy_gameapi::{Config, SteamgriddbService};
+use httpmock::MockServer;
+use reqwest::Client;
+
+pub const CELESTE: &str = r#"{"success":true,"data":{"id":13136,"name":"Celeste","types":["steam","gog"],"release_date":1516867200,"verified":true}}"#;
+
+/// The default configuration, talking to `server` instead of SteamGridDB.
+pub fn config(server: &MockServer) -> Config {
+    let mut config = Config::default();
+    config.upstream.base_url = server.base_url();
+    config
+}
+
+/// A service without caches, retries or limits, talking to `server`.
+pub fn service(server: &MockServer) -> SteamgriddbService {
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    SteamgriddbService::new(Arc::new(client), server.base_url())
+}
@@ -0,0 +1,190 @@
+use cosy_gameapi::config::{ContentRule, PaginationConfig, CONFIG_FILE_ENV};
+use cosy_gameapi::{ApiError, Config};
+
+#[test]
+fn empty_file_yields_defaults() {
+    let config = Config::from_toml("").unwrap();
+
+    assert_eq!(config.server.host, "0.0.0.0");
+    assert_eq!(config.server.port, 8080);
+    assert_eq!(config.server.workers, None);
+    assert_eq!(config.upstream.timeout_ms, 5_000);
+    assert_eq!(config.pagination.default_limit, 15);
+    assert_eq!(config.enrichment.concurrency, 8);
+}
+
+#[test]
+fn sections_override_defaults() {
+    let config = Config::from_toml(
+        r#"
+        admin_token = "secret"
+
+        [server]
+        host = "127.0.0.1"
+        port = 9000
+        workers = 2
+
+        [upstream]
+        base_url = "http://localhost:1234"
+        timeout_ms = 750
+
+        [pagination]
+        default_limit = 10
+        max_limit = 20
+
+        [enrichment]
+        concurrency = 4
+
+        [cache.logos]
+        ttl_secs = 60
+        max_entries = 10
+
+        [retry]
+        max_attempts = 5
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.server.host, "127.0.0.1");
+    assert_eq!(config.server.port, 9000);
+    assert_eq!(config.server.workers, Some(2));
+    assert_eq!(config.upstream.base_url, "http://localhost:1234");
+    assert_eq!(config.upstream.timeout_ms, 750);
+    assert_eq!(config.pagination.max_limit, 20);
+    assert_eq!(config.enrichment.concurrency, 4);
+    assert_eq!(config.cache.logos.ttl_secs, 60);
+    assert_eq!(config.cache.search.ttl_secs, 10 * 60);
+    assert_eq!(config.retry.max_attempts, 5);
+    assert_eq!(config.retry.initial_backoff_ms, 100);
+    assert_eq!(config.admin_token.as_deref(), Some("secret"));
+}
+
+#[test]
+fn partial_cache_sections_keep_the_kinds_defaults() {
+    let config =
+        Config::from_toml("[cache.logos]\nttl_secs = 60\n\n[cache.grids]\nmax_entries = 5\n")
+            .unwrap();
+
+    assert_eq!(config.cache.logos.ttl_secs, 60);
+    assert_eq!(config.cache.logos.max_entries, 5_000);
+    assert_eq!(config.cache.grids.ttl_secs, 60 * 60);
+    assert_eq!(config.cache.grids.max_entries, 5);
+
+    let err = Config::from_toml("[cache.logos]\nttl = 60\n").unwrap_err();
+    assert!(err.to_string().contains("ttl"), "{}", err);
+}
+
+#[test]
+fn unknown_keys_are_rejected() {
+    let err = Config::from_toml("[server]\nprot = 9000\n").unwrap_err();
+
+    assert!(matches!(err, ApiError::Config(_)));
+    assert!(err.to_string().contains("prot"), "{}", err);
+}
+
+#[test]
+fn invalid_values_are_rejected() {
+    let cases = [
+        (
+            "[pagination]\ndefault_limit = 50\nmax_limit = 10\n",
+            "pagination.max_limit",
+        ),
+        ("[enrichment]\nconcurrency = 0\n", "enrichment.concurrency"),
+        ("[enrichment]\ncandidates = 0\n", "enrichment.candidates"),
+        (
+            "[enrichment]\nhero_styles = [\"white_logo\"]\n",
+            "enrichment.hero_styles",
+        ),
+        (
+            "[enrichment]\nlanguage = \"english\"\n",
+            "enrichment.language",
+        ),
+        (
+            "[enrichment]\ngrid_dimensions = [\"1920x620\"]\n",
+            "enrichment.grid_dimensions",
+        ),
+        (
+            "[upstream]\nbase_url = \"ftp://example.com\"\n",
+            "upstream.base_url",
+        ),
+        (
+            "[cache.search]\nmax_entries = 0\n",
+            "cache.search.max_entries",
+        ),
+        ("[cache.heroes]\nttl_secs = 0\n", "cache.heroes.ttl_secs"),
+        (
+            "[persistent_cache]\nenabled = true\npath = \"\"\n",
+            "persistent_cache.path",
+        ),
+        (
+            "[persistent_cache]\nenabled = true\nttl_secs = 0\n",
+            "persistent_cache.ttl_secs",
+        ),
+        ("[retry]\njitter = 2.0\n", "retry.jitter"),
+        ("[retry]\ndeadline_ms = 0\n", "retry.deadline_ms"),
+        (
+            "[retry]\ninitial_backoff_ms = 500\nmax_backoff_ms = 100\n",
+            "retry.initial_backoff_ms",
+        ),
+        (
+            "[rate_limit]\nrequests_per_second = 1e-30\n",
+            "rate_limit.requests_per_second",
+        ),
+        ("[server]\nport = \"eighty\"\n", "port"),
+    ];
+
+    for (source, expected) in cases {
+        let err = Config::from_toml(source).unwrap_err();
+        assert!(err.to_string().contains(expected), "{}: {}", source, err);
+    }
+}
+
+#[test]
+fn content_policy_defaults_to_opt_in() {
+    let config = Config::from_toml("[content]\nnsfw = \"block\"\nhumor = \"allow\"\n").unwrap();
+
+    assert_eq!(config.content.nsfw, ContentRule::Block);
+    assert_eq!(config.content.humor, ContentRule::Allow);
+    assert_eq!(config.content.epilepsy, ContentRule::OptIn);
+
+    let err = Config::from_toml("[content]\nnsfw = \"sometimes\"\n").unwrap_err();
+    assert!(matches!(err, ApiError::Config(_)));
+}
+
+#[test]
+fn limits_fall_back_to_default_and_are_capped() {
+    let pagination = PaginationConfig {
+        default_limit: 15,
+        max_limit: 50,
+    };
+
+    assert_eq!(pagination.limit(None), 15);
+    assert_eq!(pagination.limit(Some(30)), 30);
+    assert_eq!(pagination.limit(Some(500)), 50);
+}
+
+#[test]
+fn environment_overrides_the_file() {
+    let path =
+        std::env::temp_dir().join(format!("cosy-gameapi-config-{}.toml", std::process::id()));
+    std::fs::write(&path, "[server]\nport = 9000\nhost = \"127.0.0.1\"\n").unwrap();
+
+    std::env::set_var(CONFIG_FILE_ENV, &path);
+    std::env::set_var("COSY_GAMEAPI_SERVER_PORT", "9100");
+    std::env::set_var("COSY_GAMEAPI_SGDB_API_KEY", "key");
+    let config = Config::load().unwrap();
+
+    assert_eq!(config.server.host, "127.0.0.1");
+    assert_eq!(config.server.port, 9100);
+    assert_eq!(config.upstream.api_key.as_deref(), Some("key"));
+
+    std::env::set_var("COSY_GAMEAPI_SERVER_PORT", "not-a-port");
+    let err = Config::load().unwrap_err();
+    assert!(
+        err.to_string().contains("COSY_GAMEAPI_SERVER_PORT"),
+        "{}",
+        err
+    );
+
+    std::fs::remove_file(path).unwrap();
+}
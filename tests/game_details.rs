@@ -0,0 +1,99 @@
+mod common;
+
+use actix_web::{http::StatusCode, test, web, App};
+use common::{config, CELESTE};
+use cosy_gameapi::routes::{self, get_game_by_id};
+use cosy_gameapi::GlobalState;
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use serde_json::Value;
+
+const HERO: &str = r#"{"success":true,"page":0,"total":1,"limit":1,"data":[{"id":7,"url":"https://example.com/hero.png","thumb":"https://example.com/hero_thumb.png","score":0,"style":"blurred","width":1920,"height":620,"nsfw":false,"humor":false,"mime":"image/png","language":"en","lock":false,"epilepsy":false,"upvotes":0,"downvotes":0,"author":{"name":"someone","steam64":"1","avatar":null}}]}"#;
+
+#[actix_web::test]
+async fn details_embed_requested_assets() {
+    let server = MockServer::start();
+    let _game = server.mock(|when, then| {
+        when.method(GET).path("/games/id/13136");
+        then.status(200).body(CELESTE);
+    });
+    let _hero = server.mock(|when, then| {
+        when.method(GET)
+            .path("/heroes/game/13136")
+            .query_param("limit", "1");
+        then.status(200).body(HERO);
+    });
+    let icons = server.mock(|when, then| {
+        when.method(GET).path("/icons/game/13136");
+        then.status(500);
+    });
+    let logos = server.mock(|when, then| {
+        when.method(GET).path("/logos/game/13136");
+        then.status(200).body(HERO);
+    });
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &config(&server)).unwrap(),
+            ))
+            .service(get_game_by_id),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/games/13136?include_hero=true&include_icon=true")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    let data = &body["data"];
+    assert_eq!(data["name"], "Celeste");
+    assert_eq!(data["release_date"], 1516867200);
+    assert_eq!(data["types"], serde_json::json!(["steam", "gog"]));
+    assert_eq!(data["verified"], true);
+    assert_eq!(data["hero"]["url"], "https://example.com/hero.png");
+    assert_eq!(data["hero_status"], "found");
+    // a failing icon lookup only leaves the icon out, and is reported
+    assert!(data.get("icon").is_none());
+    assert_eq!(data["icon_status"], "error");
+    assert_eq!(data["partial"], true);
+    assert!(data.get("logo").is_none());
+    assert!(data.get("logo_status").is_none());
+    assert!(icons.hits() >= 1);
+    logos.assert_hits(0);
+}
+
+#[actix_web::test]
+async fn unknown_game_is_not_found() {
+    let server = MockServer::start();
+    let _m = server.mock(|when, then| {
+        when.method(GET).path("/games/id/1");
+        then.status(404)
+            .body(r#"{"success":false,"errors":["Game not found"]}"#);
+    });
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &config(&server)).unwrap(),
+            ))
+            .app_data(routes::path_config())
+            .service(get_game_by_id),
+    )
+    .await;
+
+    for (uri, status, code) in [
+        ("/games/1", StatusCode::NOT_FOUND, "GAME_NOT_FOUND"),
+        ("/games/celeste", StatusCode::BAD_REQUEST, "INVALID_QUERY"),
+    ] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(resp.status(), status, "{}", uri);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["error_code"], code, "{}", uri);
+    }
+}
@@ -1,5 +1,10 @@
-use cosy_gameapi::Game;
+use actix_web::{http::StatusCode, test, web, App};
+use cosy_gameapi::routes::{self, search_games};
+use cosy_gameapi::{Config, Game, GlobalState};
 use futures::StreamExt;
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use serde_json::Value;
 
 #[tokio::test]
 async fn parallel_fetch_sets_logo() {
@@ -7,20 +12,50 @@ async fn parallel_fetch_sets_logo() {
         Game {
             id: 1,
             name: "a".into(),
+            release_date: None,
+            release_year: None,
+            verified: false,
+            types: None,
             logo_url: None,
             hero_url: None,
+            grid_url: None,
+            icon_url: None,
+            logo_status: None,
+            hero_status: None,
+            grid_status: None,
+            icon_status: None,
         },
         Game {
             id: 2,
             name: "b".into(),
+            release_date: None,
+            release_year: None,
+            verified: false,
+            types: None,
             logo_url: None,
             hero_url: None,
+            grid_url: None,
+            icon_url: None,
+            logo_status: None,
+            hero_status: None,
+            grid_status: None,
+            icon_status: None,
         },
         Game {
             id: 3,
             name: "c".into(),
+            release_date: None,
+            release_year: None,
+            verified: false,
+            types: None,
             logo_url: None,
             hero_url: None,
+            grid_url: None,
+            icon_url: None,
+            logo_status: None,
+            hero_status: None,
+            grid_status: None,
+            icon_status: None,
         },
     ];
 
@@ -55,14 +90,34 @@ async fn parallel_fetch_sets_logo_and_hero() {
         Game {
             id: 10,
             name: "x".into(),
+            release_date: None,
+            release_year: None,
+            verified: false,
+            types: None,
             logo_url: None,
             hero_url: None,
+            grid_url: None,
+            icon_url: None,
+            logo_status: None,
+            hero_status: None,
+            grid_status: None,
+            icon_status: None,
         },
         Game {
             id: 11,
             name: "y".into(),
+            release_date: None,
+            release_year: None,
+            verified: false,
+            types: None,
             logo_url: None,
             hero_url: None,
+            grid_url: None,
+            icon_url: None,
+            logo_status: None,
+            hero_status: None,
+            grid_status: None,
+            icon_status: None,
         },
     ];
 
@@ -88,3 +143,331 @@ async fn parallel_fetch_sets_logo_and_hero() {
         assert!(g.hero_url.is_some());
     }
 }
+
+#[actix_web::test]
+async fn search_results_carry_release_year_and_verified_flag() {
+    let server = MockServer::start();
+    let _m = server.mock(|when, then| {
+        when.method(GET).path("/search/autocomplete/doom");
+        then.status(200).body(
+            r#"{"success":true,"data":[
+                {"id":1,"name":"DOOM","release_date":1463097600,"types":["steam"],"verified":true},
+                {"id":2,"name":"DOOM","release_date":755913600,"types":["steam"],"verified":true},
+                {"id":3,"name":"DOOM fan remake","release_date":null,"types":[],"verified":false}
+            ]}"#,
+        );
+    });
+
+    let mut config = Config::default();
+    config.upstream.base_url = server.base_url();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(GlobalState::new("dummy", &config).unwrap()))
+            .service(search_games),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/games?query=doom")
+            .to_request(),
+    )
+    .await;
+    let body: Value = test::read_body_json(resp).await;
+    let games = body["data"]["games"].as_array().unwrap();
+    assert_eq!(games.len(), 3);
+    assert_eq!(games[0]["release_year"], 2016);
+    assert_eq!(games[1]["release_year"], 1993);
+    assert_eq!(games[0]["verified"], true);
+    assert!(games[2].get("release_date").is_none());
+    assert!(games[2].get("release_year").is_none());
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/games?query=doom&verified_only=true")
+            .to_request(),
+    )
+    .await;
+    let body: Value = test::read_body_json(resp).await;
+    let ids: Vec<u64> = body["data"]["games"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|game| game["id"].as_u64().unwrap())
+        .collect();
+    assert_eq!(ids, [1, 2]);
+}
+
+#[actix_web::test]
+async fn invalid_selection_preferences_are_rejected() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &Config::default()).unwrap(),
+            ))
+            .app_data(routes::query_config())
+            .service(search_games),
+    )
+    .await;
+
+    for uri in [
+        "/games?query=doom&hero_style=white_logo",
+        "/games?query=doom&logo_style=blurred",
+        "/games?query=doom&hero_dimensions=600x900",
+        "/games?query=doom&grid_style=official",
+        "/games?query=doom&language=english",
+        "/games?query=doom&rank_by=newest",
+    ] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", uri);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["error_code"], "INVALID_QUERY", "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn enrichment_skips_flagged_heroes_unless_opted_in() {
+    let server = MockServer::start();
+    let _search = server.mock(|when, then| {
+        when.method(GET).path("/search/autocomplete/celeste");
+        then.status(200).body(
+            r#"{"success":true,"data":[{"id":7,"name":"Celeste","types":["steam"],"verified":true}]}"#,
+        );
+    });
+    let safe = server.mock(|when, then| {
+        when.method(GET)
+            .path("/heroes/game/7")
+            .query_param("nsfw", "false")
+            .query_param("epilepsy", "false");
+        then.status(200)
+            .body(heroes("https://example.com/safe.png"));
+    });
+    let any = server.mock(|when, then| {
+        when.method(GET)
+            .path("/heroes/game/7")
+            .query_param("nsfw", "any");
+        then.status(200).body(heroes("https://example.com/any.png"));
+    });
+
+    let mut config = Config::default();
+    config.upstream.base_url = server.base_url();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(GlobalState::new("dummy", &config).unwrap()))
+            .service(search_games),
+    )
+    .await;
+
+    for (uri, url) in [
+        (
+            "/games?query=celeste&include_hero=true",
+            "https://example.com/safe.png",
+        ),
+        (
+            "/games?query=celeste&include_hero=true&nsfw=any",
+            "https://example.com/any.png",
+        ),
+    ] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["data"]["games"][0]["hero_url"], url, "{}", uri);
+    }
+    safe.assert_hits(1);
+    any.assert_hits(1);
+}
+
+#[actix_web::test]
+async fn grids_and_icons_are_picked_by_preference() {
+    let server = MockServer::start();
+    let _search = server.mock(|when, then| {
+        when.method(GET).path("/search/autocomplete/celeste");
+        then.status(200).body(
+            r#"{"success":true,"data":[{"id":7,"name":"Celeste","types":["steam"],"verified":true}]}"#,
+        );
+    });
+    let grids = server.mock(|when, then| {
+        when.method(GET).path("/grids/game/7");
+        then.status(200).body(format!(
+            r#"{{"success":true,"page":0,"total":2,"limit":10,"data":[{},{}]}}"#,
+            asset(1, 920, 430, 9),
+            asset(2, 600, 900, 1)
+        ));
+    });
+    let icons = server.mock(|when, then| {
+        when.method(GET).path("/icons/game/7");
+        then.status(200).body(format!(
+            r#"{{"success":true,"page":0,"total":1,"limit":10,"data":[{}]}}"#,
+            asset(3, 256, 256, 0)
+        ));
+    });
+
+    let mut config = Config::default();
+    config.upstream.base_url = server.base_url();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(GlobalState::new("dummy", &config).unwrap()))
+            .service(search_games),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/games?query=celeste&include_grid=true&include_icon=true&grid_dimensions=600x900")
+            .to_request(),
+    )
+    .await;
+    let body: Value = test::read_body_json(resp).await;
+    let game = &body["data"]["games"][0];
+    assert_eq!(game["grid_url"], "https://example.com/2.png");
+    assert_eq!(game["icon_url"], "https://example.com/3.png");
+    assert!(game.get("logo_url").is_none());
+    grids.assert_hits(1);
+    icons.assert_hits(1);
+}
+
+fn asset(id: u32, width: u32, height: u32, score: u32) -> String {
+    format!(
+        r#"{{"id":{},"url":"https://example.com/{}.png","thumb":"thumb","score":{},"style":"alternate","width":{},"height":{},"nsfw":false,"humor":false,"mime":"image/png","language":"en","lock":false,"epilepsy":false,"upvotes":0,"downvotes":0,"author":{{"name":"","steam64":"","avatar":null}}}}"#,
+        id, id, score, width, height
+    )
+}
+
+fn heroes(url: &str) -> String {
+    format!(
+        r#"{{"success":true,"page":0,"total":1,"limit":1,"data":[{{"id":1,"url":"{}","thumb":"{}","score":0,"style":"blurred","width":1920,"height":620,"nsfw":false,"humor":false,"mime":"image/png","language":"en","lock":false,"epilepsy":false,"upvotes":0,"downvotes":0,"author":{{"name":"someone","steam64":"1","avatar":null}}}}]}}"#,
+        url, url
+    )
+}
+
+#[actix_web::test]
+async fn include_selects_enrichments() {
+    let server = MockServer::start();
+    let _search = server.mock(|when, then| {
+        when.method(GET).path("/search/autocomplete/celeste");
+        then.status(200).body(
+            r#"{"success":true,"data":[{"id":7,"name":"Celeste","types":["steam","gog"],"verified":true}]}"#,
+        );
+    });
+    let heroes = server.mock(|when, then| {
+        when.method(GET).path("/heroes/game/7");
+        then.status(200)
+            .body(heroes("https://example.com/hero.png"));
+    });
+    let icons = server.mock(|when, then| {
+        when.method(GET).path("/icons/game/7");
+        then.status(200).body(format!(
+            r#"{{"success":true,"page":0,"total":1,"limit":10,"data":[{}]}}"#,
+            asset(3, 256, 256, 0)
+        ));
+    });
+    let logos = server.mock(|when, then| {
+        when.method(GET).path("/logos/game/7");
+        then.status(500);
+    });
+
+    let mut config = Config::default();
+    config.upstream.base_url = server.base_url();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(GlobalState::new("dummy", &config).unwrap()))
+            .service(search_games),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/games?query=celeste&include=icon,details&include_hero=true")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    let game = &body["data"]["games"][0];
+    assert_eq!(game["hero_url"], "https://example.com/hero.png");
+    assert_eq!(game["icon_url"], "https://example.com/3.png");
+    assert_eq!(game["types"], serde_json::json!(["steam", "gog"]));
+    heroes.assert_hits(1);
+    icons.assert_hits(1);
+    logos.assert_hits(0);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/games?query=celeste&include=logo,poster")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error_code"], "INVALID_QUERY");
+    assert_eq!(
+        body["message"],
+        "unknown include 'poster', expected one of: logo, hero, grid, icon, details"
+    );
+}
+
+#[actix_web::test]
+async fn failed_enrichment_is_reported() {
+    let server = MockServer::start();
+    let _search = server.mock(|when, then| {
+        when.method(GET).path("/search/autocomplete/celeste");
+        then.status(200).body(
+            r#"{"success":true,"data":[{"id":7,"name":"Celeste","types":["steam"],"verified":true}]}"#,
+        );
+    });
+    let _heroes = server.mock(|when, then| {
+        when.method(GET).path("/heroes/game/7");
+        then.status(200)
+            .body(heroes("https://example.com/hero.png"));
+    });
+    let _icons = server.mock(|when, then| {
+        when.method(GET).path("/icons/game/7");
+        then.status(200)
+            .body(r#"{"success":true,"page":0,"total":0,"limit":10,"data":[]}"#);
+    });
+    let _logos = server.mock(|when, then| {
+        when.method(GET).path("/logos/game/7");
+        then.status(500);
+    });
+
+    let mut config = Config::default();
+    config.upstream.base_url = server.base_url();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(GlobalState::new("dummy", &config).unwrap()))
+            .service(search_games),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/games?query=celeste&include=hero,icon")
+            .to_request(),
+    )
+    .await;
+    let body: Value = test::read_body_json(resp).await;
+    let game = &body["data"]["games"][0];
+    assert_eq!(game["hero_status"], "found");
+    assert_eq!(game["icon_status"], "none");
+    assert!(game.get("logo_status").is_none());
+    assert_eq!(body["data"]["partial"], false);
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/games?query=celeste&include=logo,hero")
+            .to_request(),
+    )
+    .await;
+    let body: Value = test::read_body_json(resp).await;
+    let game = &body["data"]["games"][0];
+    assert_eq!(game["logo_status"], "error");
+    assert!(game.get("logo_url").is_none());
+    assert_eq!(game["hero_status"], "found");
+    assert_eq!(body["data"]["partial"], true);
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use actix_web::{http::StatusCode, test, web, App};
+use common::{config, service};
+use cosy_gameapi::routes::{liveness, readiness};
+use cosy_gameapi::services::health::UpstreamProbe;
+use cosy_gameapi::GlobalState;
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use serde_json::Value;
+use std::time::Duration;
+
+#[actix_web::test]
+async fn liveness_always_answers() {
+    let app = test::init_service(App::new().service(liveness)).await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get().uri("/health/live").to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"]["status"], "ok");
+}
+
+#[actix_web::test]
+async fn readiness_reports_components_and_probes_upstream_in_background() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET).path_contains("/search/autocomplete/");
+        then.status(200).body(r#"{"success":true,"data":[]}"#);
+    });
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &config(&server)).unwrap(),
+            ))
+            .service(readiness),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get().uri("/health/ready").to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["success"], true);
+    assert_eq!(body["data"]["ready"], true);
+    assert_eq!(body["data"]["config"]["status"], "ok");
+    assert_eq!(body["data"]["cache"]["status"], "ok");
+    assert_eq!(body["data"]["persistent_cache"]["status"], "disabled");
+    assert_eq!(body["data"]["upstream"]["status"], "unknown");
+
+    while m.hits() == 0 {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    for _ in 0..3 {
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get().uri("/health/ready").to_request(),
+        )
+        .await;
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["data"]["upstream"]["status"], "ok");
+    }
+    m.assert_hits(1);
+}
+
+#[actix_web::test]
+async fn readiness_reads_the_persistent_cache() {
+    let server = MockServer::start();
+    let _m = server.mock(|when, then| {
+        when.method(GET).path_contains("/search/autocomplete/");
+        then.status(200).body(r#"{"success":true,"data":[]}"#);
+    });
+
+    let mut config = config(&server);
+    config.persistent_cache.enabled = true;
+    config.persistent_cache.path = std::env::temp_dir().join(format!(
+        "cosy-gameapi-readiness-{}.redb",
+        std::process::id()
+    ));
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(GlobalState::new("dummy", &config).unwrap()))
+            .service(readiness),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get().uri("/health/ready").to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"]["ready"], true);
+    assert_eq!(body["data"]["persistent_cache"]["status"], "ok");
+}
+
+#[tokio::test]
+async fn failed_probe_is_remembered() {
+    let server = MockServer::start();
+    let _m = server.mock(|when, then| {
+        when.method(GET).path_contains("/search/autocomplete/");
+        then.status(500);
+    });
+
+    let service = service(&server);
+    let probe = UpstreamProbe::new(Duration::from_secs(60));
+
+    assert!(probe.last().is_none());
+    probe.run(&service).await;
+
+    let last = probe.last().unwrap();
+    assert!(last.error.unwrap().contains("500"));
+}
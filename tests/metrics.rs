@@ -0,0 +1,62 @@
+use actix_web::{middleware::from_fn, test, web, App};
+use cosy_gameapi::metrics::track_requests;
+use cosy_gameapi::routes::{prometheus_metrics, search_games};
+use cosy_gameapi::{Config, GlobalState};
+use httpmock::Method::GET;
+use httpmock::MockServer;
+
+#[actix_web::test]
+async fn metrics_cover_requests_upstream_enrichment_and_cache() {
+    let server = MockServer::start();
+    let _search = server.mock(|when, then| {
+        when.method(GET).path("/search/autocomplete/doom");
+        then.status(200).body(r#"{"success":true,"data":[{"id":1,"name":"DOOM","release_date":742262400,"verified":true,"types":["steam"]}]}"#);
+    });
+    let _logos = server.mock(|when, then| {
+        when.method(GET).path("/logos/game/1");
+        then.status(404);
+    });
+
+    let mut config = Config::default();
+    config.upstream.base_url = server.base_url();
+    let app = test::init_service(
+        App::new()
+            .wrap(from_fn(track_requests))
+            .app_data(web::Data::new(GlobalState::new("dummy", &config).unwrap()))
+            .service(search_games)
+            .service(prometheus_metrics),
+    )
+    .await;
+
+    for _ in 0..2 {
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri("/games?query=doom&include_logo=true")
+                .to_request(),
+        )
+        .await;
+        assert!(resp.status().is_success());
+    }
+
+    let resp =
+        test::call_service(&app, test::TestRequest::get().uri("/metrics").to_request()).await;
+    assert!(resp.status().is_success());
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+
+    for expected in [
+        r#"cosy_gameapi_http_requests_total{method="GET",route="/games",status="200"} 2"#,
+        r#"cosy_gameapi_http_request_duration_seconds_count{method="GET",route="/games"} 2"#,
+        r#"cosy_gameapi_upstream_requests_total{endpoint="search",status="200"} 1"#,
+        r#"cosy_gameapi_upstream_requests_total{endpoint="logos",status="404"} 2"#,
+        r#"cosy_gameapi_upstream_request_duration_seconds_count{endpoint="search"} 1"#,
+        r#"cosy_gameapi_enrichments_total{kind="logo",outcome="failed"} 2"#,
+        r#"cosy_gameapi_cache_hits_total{cache="search"} 1"#,
+        r#"cosy_gameapi_cache_misses_total{cache="search"} 1"#,
+        r#"cosy_gameapi_cache_hit_ratio{cache="search"} 0.5"#,
+        r#"cosy_gameapi_circuit_breaker_state{state="closed"} 1"#,
+        r#"cosy_gameapi_rate_limit_queued 0"#,
+    ] {
+        assert!(body.contains(expected), "missing {}\n{}", expected, body);
+    }
+}
@@ -0,0 +1,163 @@
+use cosy_gameapi::config::{CacheConfig, PersistentCacheConfig};
+use cosy_gameapi::services::asset_filter::AssetFilter;
+use cosy_gameapi::services::cache::UpstreamCache;
+use cosy_gameapi::services::persistent_cache::{Lookup, PersistentCache, SCHEMA_VERSION};
+use cosy_gameapi::services::selection::Selection;
+use cosy_gameapi::SteamgriddbService;
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use reqwest::Client;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn config(name: &str, ttl_secs: u64, stale_secs: u64) -> PersistentCacheConfig {
+    let path: PathBuf =
+        std::env::temp_dir().join(format!("cosy-gameapi-{}-{}.redb", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    PersistentCacheConfig {
+        enabled: true,
+        path,
+        ttl_secs,
+        stale_secs,
+    }
+}
+
+#[tokio::test]
+async fn entries_survive_reopening() {
+    let config = config("reopen", 60, 60);
+
+    let store = PersistentCache::open(&config).unwrap();
+    store
+        .insert("search::doom", &vec!["DOOM".to_string()])
+        .await;
+    drop(store);
+
+    let store = PersistentCache::open(&config).unwrap();
+    match store.get::<Vec<String>>("search::doom").await {
+        Lookup::Fresh(value) => assert_eq!(value, vec!["DOOM".to_string()]),
+        _ => panic!("expected a fresh entry"),
+    }
+    assert!(matches!(
+        store.get::<Vec<String>>("search::quake").await,
+        Lookup::Miss
+    ));
+}
+
+#[tokio::test]
+async fn expired_entries_are_stale_then_gone() {
+    let store = PersistentCache::open(&config("stale", 0, 60)).unwrap();
+    store.insert("grids:1:", &1u32).await;
+    assert!(matches!(
+        store.get::<u32>("grids:1:").await,
+        Lookup::Stale(1)
+    ));
+
+    let store = PersistentCache::open(&config("gone", 0, 0)).unwrap();
+    store.insert("grids:1:", &1u32).await;
+    assert!(matches!(store.get::<u32>("grids:1:").await, Lookup::Miss));
+}
+
+#[tokio::test]
+async fn entries_with_their_own_ttl_are_never_stale() {
+    let store = PersistentCache::open(&config("own-ttl", 60, 60)).unwrap();
+    store
+        .insert_with_ttl("heroes:1:", &1u32, Duration::ZERO)
+        .await;
+    assert!(matches!(store.get::<u32>("heroes:1:").await, Lookup::Miss));
+
+    store
+        .insert_with_ttl("heroes:2:", &2u32, Duration::from_secs(60))
+        .await;
+    assert!(matches!(
+        store.get::<u32>("heroes:2:").await,
+        Lookup::Fresh(2)
+    ));
+}
+
+#[tokio::test]
+async fn schema_change_drops_old_entries() {
+    let config = config("schema", 60, 60);
+
+    {
+        let db = redb::Database::create(&config.path).unwrap();
+        let txn = db.begin_write().unwrap();
+        {
+            let mut meta = txn
+                .open_table(redb::TableDefinition::<&str, u32>::new("meta"))
+                .unwrap();
+            meta.insert("schema_version", SCHEMA_VERSION + 1).unwrap();
+            let mut entries = txn
+                .open_table(redb::TableDefinition::<&str, &[u8]>::new("entries"))
+                .unwrap();
+            entries.insert("search::doom", b"[]".as_slice()).unwrap();
+        }
+        txn.commit().unwrap();
+    }
+
+    let store = PersistentCache::open(&config).unwrap();
+    assert!(matches!(
+        store.get::<Vec<String>>("search::doom").await,
+        Lookup::Miss
+    ));
+}
+
+#[tokio::test]
+async fn stale_search_is_served_and_revalidated() {
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/search/autocomplete/celeste");
+        then.status(200).body(r#"{"success":true,"data":[{"id":13136,"name":"Celeste","release_date":1516867200,"verified":true,"types":["steam"]}]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let store = Arc::new(PersistentCache::open(&config("revalidate", 0, 60)).unwrap());
+    let service =
+        SteamgriddbService::new(Arc::new(client), server.base_url()).with_persistent_cache(store);
+
+    let first = service.search("celeste").await.unwrap();
+    m.assert_hits(1);
+
+    let second = service.search("celeste").await.unwrap();
+    assert_eq!(first, second);
+
+    // the stale hit triggers a background refresh
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    m.assert_hits(2);
+}
+
+#[tokio::test]
+async fn missing_hero_is_persisted_only_for_the_negative_ttl() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/heroes/game/3");
+        then.status(200)
+            .body(r#"{"success":true,"page":0,"total":0,"limit":1,"data":[]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let cache = CacheConfig {
+        negative_ttl_secs: 0,
+        ..CacheConfig::default()
+    };
+    let store = Arc::new(PersistentCache::open(&config("negative", 60, 60)).unwrap());
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url())
+        .with_cache(Arc::new(UpstreamCache::new(&cache)))
+        .with_persistent_cache(store);
+
+    for _ in 0..2 {
+        let hero = service
+            .get_best_hero_by_game_id(3, &AssetFilter::default(), &Selection::default())
+            .await
+            .unwrap();
+        assert!(hero.is_none());
+    }
+    m.assert_hits(2);
+}
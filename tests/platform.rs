@@ -0,0 +1,137 @@
+mod common;
+
+use actix_web::{http::StatusCode, test, web, App};
+use common::{config, CELESTE};
+use cosy_gameapi::routes::{self, get_assets_by_platform_id, get_game_by_platform_id};
+use cosy_gameapi::GlobalState;
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use serde_json::Value;
+
+#[actix_web::test]
+async fn game_is_found_by_store_id() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/games/egs/e0a1b2c3");
+        then.status(200).body(CELESTE);
+    });
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &config(&server)).unwrap(),
+            ))
+            .app_data(routes::path_config())
+            .service(get_game_by_platform_id),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/games/platform/epic/e0a1b2c3")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"]["id"], 13136);
+    assert_eq!(body["data"]["name"], "Celeste");
+    m.assert_hits(1);
+}
+
+#[actix_web::test]
+async fn unknown_platforms_and_ids_are_rejected() {
+    let server = MockServer::start();
+    let _m = server.mock(|when, then| {
+        when.method(GET).path("/games/steam/999999999");
+        then.status(404)
+            .body(r#"{"success":false,"errors":["Game not found"]}"#);
+    });
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &config(&server)).unwrap(),
+            ))
+            .app_data(routes::path_config())
+            .service(get_game_by_platform_id)
+            .service(get_assets_by_platform_id),
+    )
+    .await;
+
+    for (uri, status, code) in [
+        (
+            "/games/platform/itch/1",
+            StatusCode::BAD_REQUEST,
+            "INVALID_QUERY",
+        ),
+        (
+            "/games/platform/steam/12%3F3",
+            StatusCode::BAD_REQUEST,
+            "INVALID_QUERY",
+        ),
+        (
+            "/games/platform/steam/..",
+            StatusCode::BAD_REQUEST,
+            "INVALID_QUERY",
+        ),
+        (
+            "/assets/platform/steam/%2E%2E",
+            StatusCode::BAD_REQUEST,
+            "INVALID_QUERY",
+        ),
+        (
+            "/games/platform/steam/999999999",
+            StatusCode::NOT_FOUND,
+            "GAME_NOT_FOUND",
+        ),
+        (
+            "/assets/platform/steam/999999999",
+            StatusCode::NOT_FOUND,
+            "GAME_NOT_FOUND",
+        ),
+    ] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(resp.status(), status, "{}", uri);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["error_code"], code, "{}", uri);
+    }
+}
+
+#[actix_web::test]
+async fn assets_are_listed_by_store_id() {
+    let server = MockServer::start();
+    let _game = server.mock(|when, then| {
+        when.method(GET).path("/games/steam/504230");
+        then.status(200).body(CELESTE);
+    });
+    let grids = server.mock(|when, then| {
+        when.method(GET).path("/grids/game/13136");
+        then.status(200)
+            .body(r#"{"success":true,"page":0,"total":0,"limit":15,"data":[]}"#);
+    });
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &config(&server)).unwrap(),
+            ))
+            .service(get_assets_by_platform_id),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/assets/platform/steam/504230")
+            .to_request(),
+    )
+    .await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"]["total"], 0);
+    grids.assert_hits(1);
+}
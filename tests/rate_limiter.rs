@@ -0,0 +1,150 @@
+mod common;
+
+use cosy_gameapi::services::asset_filter::AssetFilter;
+use cosy_gameapi::services::rate_limiter::{parse_retry_after, RateLimiter, MAX_RETRY_AFTER};
+use cosy_gameapi::services::retry::RetryPolicy;
+use cosy_gameapi::services::selection::Selection;
+use cosy_gameapi::{ApiError, Endpoint, SteamgriddbService};
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+fn service(server: &MockServer, policy: RetryPolicy) -> SteamgriddbService {
+    common::service(server)
+        .with_retry_policy(policy)
+        .with_rate_limiter(Arc::new(RateLimiter::new(100.0, 10, 10)))
+}
+
+fn policy(deadline: Duration) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 2,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(10),
+        jitter: 0.0,
+        deadline,
+    }
+}
+
+#[test]
+fn retry_after_accepts_seconds_and_dates() {
+    assert_eq!(parse_retry_after(" 7 "), Some(Duration::from_secs(7)));
+    assert_eq!(
+        parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+        Some(Duration::ZERO)
+    );
+
+    let in_a_minute = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(60));
+    let parsed = parse_retry_after(&in_a_minute).unwrap();
+    assert!(parsed > Duration::from_secs(55) && parsed <= Duration::from_secs(60));
+
+    assert_eq!(parse_retry_after("soon"), None);
+}
+
+#[test]
+fn huge_retry_after_is_capped() {
+    assert_eq!(
+        parse_retry_after("18446744073709551615"),
+        Some(MAX_RETRY_AFTER)
+    );
+
+    let limiter = RateLimiter::new(20.0, 1, 10);
+    limiter.pause(Duration::MAX);
+}
+
+#[tokio::test]
+async fn requests_beyond_the_burst_wait_for_tokens() {
+    let limiter = RateLimiter::new(20.0, 1, 10);
+
+    let started = Instant::now();
+    for _ in 0..3 {
+        limiter.acquire(Endpoint::Search, None).await.unwrap();
+    }
+
+    assert!(started.elapsed() >= Duration::from_millis(90));
+}
+
+#[tokio::test]
+async fn tiny_rates_wait_at_most_the_retry_after_cap() {
+    let limiter = RateLimiter::new(1e-30, 1, 1);
+    limiter.acquire(Endpoint::Search, None).await.unwrap();
+
+    for _ in 0..2 {
+        let res = tokio::time::timeout(
+            Duration::from_millis(10),
+            limiter.acquire(Endpoint::Search, None),
+        )
+        .await;
+        assert!(res.is_err());
+    }
+    assert_eq!(limiter.queued(), 0);
+}
+
+#[tokio::test]
+async fn full_queue_rejects_with_throttled() {
+    let limiter = Arc::new(RateLimiter::new(1.0, 1, 1));
+    limiter.acquire(Endpoint::Search, None).await.unwrap();
+
+    let waiting = {
+        let limiter = limiter.clone();
+        tokio::spawn(async move { limiter.acquire(Endpoint::Search, None).await })
+    };
+    while limiter.queued() == 0 {
+        tokio::task::yield_now().await;
+    }
+
+    let res = limiter.acquire(Endpoint::Logos, Some(1)).await;
+    assert!(matches!(
+        res,
+        Err(ApiError::Throttled {
+            endpoint: Endpoint::Logos,
+            game_id: Some(1)
+        })
+    ));
+
+    waiting.abort();
+    let _ = waiting.await;
+    assert_eq!(limiter.queued(), 0);
+}
+
+#[tokio::test]
+async fn retry_waits_for_retry_after() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/logos/game/1");
+        then.status(429).header("Retry-After", "1");
+    });
+
+    let started = Instant::now();
+    let res = service(&server, policy(Duration::from_secs(5)))
+        .get_best_logo_by_game_id(1, &AssetFilter::default(), &Selection::default())
+        .await;
+
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert!(matches!(
+        res,
+        Err(ApiError::UpstreamRateLimited {
+            retry_after: Some(d),
+            ..
+        }) if d == Duration::from_secs(1)
+    ));
+    m.assert_hits(2);
+}
+
+#[tokio::test]
+async fn retry_after_beyond_deadline_gives_up() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/heroes/game/1");
+        then.status(429).header("Retry-After", "30");
+    });
+
+    let started = Instant::now();
+    let res = service(&server, policy(Duration::from_secs(2)))
+        .get_best_hero_by_game_id(1, &AssetFilter::default(), &Selection::default())
+        .await;
+
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(matches!(res, Err(ApiError::UpstreamRateLimited { .. })));
+    m.assert_hits(1);
+}
@@ -0,0 +1,81 @@
+use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+use actix_web::{middleware::from_fn, web, App};
+use cosy_gameapi::routes::{self, liveness, search_games};
+use cosy_gameapi::telemetry::{trace_requests, RequestId};
+use cosy_gameapi::{Config, GlobalState};
+use serde_json::Value;
+
+#[test]
+fn client_ids_are_validated() {
+    assert!(RequestId::from_client("abc-123_x.y:z").is_some());
+    assert!(RequestId::from_client("").is_none());
+    assert!(RequestId::from_client("has space").is_none());
+    assert!(RequestId::from_client(&"a".repeat(129)).is_none());
+}
+
+#[actix_web::test]
+async fn request_id_is_generated_or_echoed() {
+    let app = init_service(App::new().wrap(from_fn(trace_requests)).service(liveness)).await;
+
+    let resp = call_service(&app, TestRequest::get().uri("/health/live").to_request()).await;
+    let generated = resp
+        .headers()
+        .get("x-request-id")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert_eq!(generated.len(), 36);
+
+    let resp = call_service(
+        &app,
+        TestRequest::get()
+            .uri("/health/live")
+            .insert_header(("X-Request-Id", "client-42"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.headers().get("x-request-id").unwrap(), "client-42");
+
+    let resp = call_service(
+        &app,
+        TestRequest::get()
+            .uri("/health/live")
+            .insert_header(("X-Request-Id", "not valid!"))
+            .to_request(),
+    )
+    .await;
+    assert_ne!(resp.headers().get("x-request-id").unwrap(), "not valid!");
+}
+
+#[actix_web::test]
+async fn error_bodies_carry_the_request_id() {
+    let app = init_service(
+        App::new()
+            .wrap(from_fn(trace_requests))
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &Config::default()).unwrap(),
+            ))
+            .app_data(routes::query_config())
+            .service(search_games)
+            .service(liveness),
+    )
+    .await;
+
+    for uri in ["/games?query=", "/games?limit=abc"] {
+        let resp = call_service(
+            &app,
+            TestRequest::get()
+                .uri(uri)
+                .insert_header(("X-Request-Id", "trace-me"))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.headers().get("x-request-id").unwrap(), "trace-me");
+        let body: Value = read_body_json(resp).await;
+        assert_eq!(body["request_id"], "trace-me", "{}", uri);
+    }
+
+    let resp = call_service(&app, TestRequest::get().uri("/health/live").to_request()).await;
+    let body: Value = read_body_json(resp).await;
+    assert!(body.get("request_id").is_none());
+}
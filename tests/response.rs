@@ -0,0 +1,192 @@
+use actix_web::{body::to_bytes, http::StatusCode, test, web, App, Responder};
+use cosy_gameapi::{
+    routes::{self, get_assets_by_id, refresh_game, search_games},
+    ApiError, Config, Endpoint, GlobalState, Response,
+};
+use serde_json::Value;
+
+async fn render(err: ApiError) -> (StatusCode, Value) {
+    let req = test::TestRequest::default().to_http_request();
+    let resp = Response::<()>::from(err).respond_to(&req);
+    let status = resp.status();
+    let body = to_bytes(resp.into_body()).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[actix_web::test]
+async fn unknown_game_maps_to_not_found() {
+    let (status, body) = render(ApiError::UpstreamStatus {
+        endpoint: Endpoint::Grids,
+        game_id: Some(42),
+        status: 404,
+    })
+    .await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["success"], false);
+    assert_eq!(body["error_code"], "GAME_NOT_FOUND");
+}
+
+#[actix_web::test]
+async fn upstream_failures_map_to_gateway_statuses() {
+    let cases = [
+        (
+            ApiError::Timeout {
+                endpoint: Endpoint::Search,
+                game_id: None,
+            },
+            StatusCode::GATEWAY_TIMEOUT,
+            "UPSTREAM_TIMEOUT",
+        ),
+        (
+            ApiError::UpstreamStatus {
+                endpoint: Endpoint::Logos,
+                game_id: Some(1),
+                status: 429,
+            },
+            StatusCode::SERVICE_UNAVAILABLE,
+            "UPSTREAM_RATE_LIMITED",
+        ),
+        (
+            ApiError::UpstreamStatus {
+                endpoint: Endpoint::Heroes,
+                game_id: Some(1),
+                status: 500,
+            },
+            StatusCode::BAD_GATEWAY,
+            "UPSTREAM_ERROR",
+        ),
+        (
+            ApiError::Decode {
+                endpoint: Endpoint::Search,
+                game_id: None,
+                message: "bad".into(),
+            },
+            StatusCode::BAD_GATEWAY,
+            "UPSTREAM_ERROR",
+        ),
+        (
+            ApiError::CircuitOpen {
+                endpoint: Endpoint::Grids,
+                game_id: Some(1),
+            },
+            StatusCode::SERVICE_UNAVAILABLE,
+            "UPSTREAM_UNAVAILABLE",
+        ),
+        (
+            ApiError::Throttled {
+                endpoint: Endpoint::Search,
+                game_id: None,
+            },
+            StatusCode::SERVICE_UNAVAILABLE,
+            "THROTTLED",
+        ),
+        (
+            ApiError::UpstreamRateLimited {
+                endpoint: Endpoint::Search,
+                game_id: None,
+                retry_after: None,
+            },
+            StatusCode::SERVICE_UNAVAILABLE,
+            "UPSTREAM_RATE_LIMITED",
+        ),
+    ];
+
+    for (err, expected_status, expected_code) in cases {
+        let (status, body) = render(err).await;
+        assert_eq!(status, expected_status);
+        assert_eq!(body["error_code"], expected_code);
+    }
+}
+
+#[actix_web::test]
+async fn bad_query_returns_invalid_query() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(
+                GlobalState::new("dummy", &Config::default()).unwrap(),
+            ))
+            .app_data(routes::query_config())
+            .app_data(routes::path_config())
+            .service(search_games)
+            .service(get_assets_by_id),
+    )
+    .await;
+
+    for uri in [
+        "/games?limit=abc",
+        "/games?query=",
+        "/assets/not-a-number",
+        "/assets/1?style=alternate,fancy",
+        "/assets/1?nsfw=maybe",
+        "/assets/1?animated=sometimes",
+    ] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{}", uri);
+        let body: Value = test::read_body_json(resp).await;
+        assert_eq!(body["error_code"], "INVALID_QUERY", "{}", uri);
+    }
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/assets/1?mime=gif")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(
+        body["message"],
+        "invalid mime 'gif', expected one of: png, jpeg, webp"
+    );
+}
+
+#[actix_web::test]
+async fn admin_refresh_requires_token() {
+    let config = Config {
+        admin_token: Some("secret".into()),
+        ..Config::default()
+    };
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(GlobalState::new("dummy", &config).unwrap()))
+            .service(refresh_game),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/admin/games/42/refresh")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["error_code"], "UNAUTHORIZED");
+
+    for wrong in ["Bearer secreT", "Bearer secret2", "Bearer "] {
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri("/admin/games/42/refresh")
+                .insert_header(("Authorization", wrong))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED, "{}", wrong);
+    }
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::post()
+            .uri("/admin/games/42/refresh")
+            .insert_header(("Authorization", "Bearer secret"))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"]["game_id"], 42);
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::service;
+use cosy_gameapi::services::asset_filter::AssetFilter;
+use cosy_gameapi::services::retry::RetryPolicy;
+use cosy_gameapi::services::selection::Selection;
+use cosy_gameapi::ApiError;
+use httpmock::Method::GET;
+use httpmock::MockServer;
+use std::time::{Duration, Instant};
+
+fn policy(max_attempts: u32, deadline: Duration) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(40),
+        jitter: 0.5,
+        deadline,
+    }
+}
+
+#[test]
+fn backoff_grows_exponentially_up_to_the_cap() {
+    let policy = RetryPolicy {
+        jitter: 0.0,
+        ..policy(5, Duration::from_secs(1))
+    };
+
+    assert_eq!(policy.backoff(1), Duration::from_millis(10));
+    assert_eq!(policy.backoff(2), Duration::from_millis(20));
+    assert_eq!(policy.backoff(3), Duration::from_millis(40));
+    assert_eq!(policy.backoff(4), Duration::from_millis(40));
+}
+
+#[test]
+fn jitter_only_shortens_backoff() {
+    let policy = policy(5, Duration::from_secs(1));
+    for _ in 0..100 {
+        let backoff = policy.backoff(2);
+        assert!(backoff <= Duration::from_millis(20));
+        assert!(backoff >= Duration::from_millis(10));
+    }
+}
+
+#[tokio::test]
+async fn server_errors_are_retried() {
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/logos/game/1");
+        then.status(502);
+    });
+
+    let res = service(&server)
+        .with_retry_policy(policy(3, Duration::from_secs(5)))
+        .get_best_logo_by_game_id(1, &AssetFilter::default(), &Selection::default())
+        .await;
+
+    assert!(matches!(
+        res,
+        Err(ApiError::UpstreamStatus { status: 502, .. })
+    ));
+    m.assert_hits(3);
+}
+
+#[tokio::test]
+async fn rate_limited_requests_are_retried() {
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/heroes/game/1");
+        then.status(429);
+    });
+
+    let _ = service(&server)
+        .with_retry_policy(policy(2, Duration::from_secs(5)))
+        .get_best_hero_by_game_id(1, &AssetFilter::default(), &Selection::default())
+        .await;
+
+    m.assert_hits(2);
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/grids/game/1");
+        then.status(404);
+    });
+
+    let res = service(&server)
+        .with_retry_policy(policy(3, Duration::from_secs(5)))
+        .fetch_assets_by_game_id(1, &AssetFilter::default(), 0, 15)
+        .await;
+
+    assert!(matches!(
+        res,
+        Err(ApiError::UpstreamStatus { status: 404, .. })
+    ));
+    m.assert_hits(1);
+}
+
+#[tokio::test]
+async fn deadline_bounds_all_attempts() {
+    let server = MockServer::start();
+
+    let _m = server.mock(|when, then| {
+        when.method(GET).path("/logos/game/1");
+        then.status(200).delay(Duration::from_millis(500));
+    });
+
+    let started = Instant::now();
+    let res = service(&server)
+        .with_retry_policy(policy(10, Duration::from_millis(150)))
+        .get_best_logo_by_game_id(1, &AssetFilter::default(), &Selection::default())
+        .await;
+
+    assert!(matches!(res, Err(ApiError::Timeout { .. })));
+    assert!(started.elapsed() < Duration::from_millis(450));
+}
@@ -1,4 +1,10 @@
+use cosy_gameapi::config::Ranking;
+use cosy_gameapi::services::asset_filter::{AssetFilter, Inclusion};
+use cosy_gameapi::services::rate_limiter::RateLimiter;
+use cosy_gameapi::services::selection::Selection;
+use cosy_gameapi::services::single_flight::{RequestKey, SingleFlight};
 use cosy_gameapi::services::steamgriddb_service::SteamgriddbService;
+use cosy_gameapi::{ApiError, Endpoint};
 use httpmock::Method::GET;
 use httpmock::MockServer;
 use reqwest::Client;
@@ -12,7 +18,7 @@ async fn logo_empty_data_returns_none() {
     let _m = server.mock(|when, then| {
         when.method(GET)
             .path("/logos/game/42")
-            .query_param("limit", "1");
+            .query_param("limit", "10");
         then.status(200)
             .body(r#"{"success":true,"page":1,"total":0,"limit":1,"data":[]}"#);
     });
@@ -21,13 +27,12 @@ async fn logo_empty_data_returns_none() {
         .timeout(Duration::from_secs(5))
         .build()
         .unwrap();
-    let service = SteamgriddbService::new(
-        Arc::new(steamgriddb_api::Client::new("dummy")),
-        Arc::new(client),
-        server.base_url(),
-    );
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
 
-    let res = service.get_first_logo_by_game_id(42).await.unwrap();
+    let res = service
+        .get_best_logo_by_game_id(42, &AssetFilter::default(), &Selection::default())
+        .await
+        .unwrap();
     assert!(res.is_none());
 }
 
@@ -38,7 +43,7 @@ async fn logo_with_data_returns_url() {
     let _m = server.mock(|when, then| {
         when.method(GET)
             .path("/logos/game/99")
-            .query_param("limit", "1");
+            .query_param("limit", "10");
         then.status(200).body(r#"{"success":true,"page":1,"total":1,"limit":1,"data":[{"id":1,"url":"https://example.com/logo.png","thumb":"thumb","score":0,"style":"","width":1,"height":1,"nsfw":false,"humor":false,"mime":"image/png","language":"","lock":false,"epilepsy":false,"upvotes":0,"downvotes":0,"author":{"name":"","steam64":"","avatar":""}}]}"#);
     });
 
@@ -46,13 +51,12 @@ async fn logo_with_data_returns_url() {
         .timeout(Duration::from_secs(5))
         .build()
         .unwrap();
-    let service = SteamgriddbService::new(
-        Arc::new(steamgriddb_api::Client::new("dummy")),
-        Arc::new(client),
-        server.base_url(),
-    );
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
 
-    let res = service.get_first_logo_by_game_id(99).await.unwrap();
+    let res = service
+        .get_best_logo_by_game_id(99, &AssetFilter::default(), &Selection::default())
+        .await
+        .unwrap();
     assert_eq!(res.unwrap(), "https://example.com/logo.png");
 }
 
@@ -63,7 +67,7 @@ async fn logo_non_200_returns_err() {
     let _m = server.mock(|when, then| {
         when.method(GET)
             .path("/logos/game/500")
-            .query_param("limit", "1");
+            .query_param("limit", "10");
         then.status(500).body("internal error");
     });
 
@@ -71,13 +75,11 @@ async fn logo_non_200_returns_err() {
         .timeout(Duration::from_secs(5))
         .build()
         .unwrap();
-    let service = SteamgriddbService::new(
-        Arc::new(steamgriddb_api::Client::new("dummy")),
-        Arc::new(client),
-        server.base_url(),
-    );
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
 
-    let res = service.get_first_logo_by_game_id(500).await;
+    let res = service
+        .get_best_logo_by_game_id(500, &AssetFilter::default(), &Selection::default())
+        .await;
     assert!(res.is_err());
     let err = format!("{}", res.unwrap_err());
     assert!(err.contains("Failed to fetch"));
@@ -90,7 +92,7 @@ async fn logo_success_false_returns_err() {
     let _m = server.mock(|when, then| {
         when.method(GET)
             .path("/logos/game/400")
-            .query_param("limit", "1");
+            .query_param("limit", "10");
         then.status(200)
             .body(r#"{"success":false,"page":1,"total":0,"limit":1,"data":[]}"#);
     });
@@ -99,13 +101,11 @@ async fn logo_success_false_returns_err() {
         .timeout(Duration::from_secs(5))
         .build()
         .unwrap();
-    let service = SteamgriddbService::new(
-        Arc::new(steamgriddb_api::Client::new("dummy")),
-        Arc::new(client),
-        server.base_url(),
-    );
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
 
-    let res = service.get_first_logo_by_game_id(400).await;
+    let res = service
+        .get_best_logo_by_game_id(400, &AssetFilter::default(), &Selection::default())
+        .await;
     assert!(res.is_err());
     let err = format!("{}", res.unwrap_err());
     assert!(err.contains("success=false"));
@@ -120,7 +120,7 @@ async fn hero_empty_data_returns_none() {
     let _m = server.mock(|when, then| {
         when.method(GET)
             .path("/heroes/game/12")
-            .query_param("limit", "1");
+            .query_param("limit", "10");
         then.status(200)
             .body(r#"{"success":true,"page":1,"total":0,"limit":1,"data":[]}"#);
     });
@@ -129,13 +129,12 @@ async fn hero_empty_data_returns_none() {
         .timeout(Duration::from_secs(5))
         .build()
         .unwrap();
-    let service = SteamgriddbService::new(
-        Arc::new(steamgriddb_api::Client::new("dummy")),
-        Arc::new(client),
-        server.base_url(),
-    );
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
 
-    let res = service.get_first_hero_by_game_id(12).await.unwrap();
+    let res = service
+        .get_best_hero_by_game_id(12, &AssetFilter::default(), &Selection::default())
+        .await
+        .unwrap();
     assert!(res.is_none());
 }
 
@@ -144,7 +143,7 @@ async fn hero_with_data_returns_url() {
     let server = MockServer::start();
 
     let _m = server.mock(|when, then| {
-        when.method(GET).path("/heroes/game/7").query_param("limit", "1");
+        when.method(GET).path("/heroes/game/7").query_param("limit", "10");
         then.status(200).body(r#"{"success":true,"page":1,"total":1,"limit":1,"data":[{"id":1,"url":"https://example.com/hero.png","thumb":"thumb","score":0,"style":"","width":1,"height":1,"nsfw":false,"humor":false,"mime":"image/png","language":"","lock":false,"epilepsy":false,"upvotes":0,"downvotes":0,"author":{"name":"","steam64":"","avatar":""}}]}"#);
     });
 
@@ -152,13 +151,12 @@ async fn hero_with_data_returns_url() {
         .timeout(Duration::from_secs(5))
         .build()
         .unwrap();
-    let service = SteamgriddbService::new(
-        Arc::new(steamgriddb_api::Client::new("dummy")),
-        Arc::new(client),
-        server.base_url(),
-    );
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
 
-    let res = service.get_first_hero_by_game_id(7).await.unwrap();
+    let res = service
+        .get_best_hero_by_game_id(7, &AssetFilter::default(), &Selection::default())
+        .await
+        .unwrap();
     assert_eq!(res.unwrap(), "https://example.com/hero.png");
 }
 
@@ -169,7 +167,7 @@ async fn hero_non_200_returns_err() {
     let _m = server.mock(|when, then| {
         when.method(GET)
             .path("/heroes/game/500")
-            .query_param("limit", "1");
+            .query_param("limit", "10");
         then.status(503).body("unavailable");
     });
 
@@ -177,13 +175,11 @@ async fn hero_non_200_returns_err() {
         .timeout(Duration::from_secs(5))
         .build()
         .unwrap();
-    let service = SteamgriddbService::new(
-        Arc::new(steamgriddb_api::Client::new("dummy")),
-        Arc::new(client),
-        server.base_url(),
-    );
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
 
-    let res = service.get_first_hero_by_game_id(500).await;
+    let res = service
+        .get_best_hero_by_game_id(500, &AssetFilter::default(), &Selection::default())
+        .await;
     assert!(res.is_err());
     let err = format!("{}", res.unwrap_err());
     assert!(err.contains("Failed to fetch"));
@@ -196,7 +192,7 @@ async fn hero_success_false_returns_err() {
     let _m = server.mock(|when, then| {
         when.method(GET)
             .path("/heroes/game/400")
-            .query_param("limit", "1");
+            .query_param("limit", "10");
         then.status(200)
             .body(r#"{"success":false,"page":1,"total":0,"limit":1,"data":[]}"#);
     });
@@ -205,14 +201,543 @@ async fn hero_success_false_returns_err() {
         .timeout(Duration::from_secs(5))
         .build()
         .unwrap();
-    let service = SteamgriddbService::new(
-        Arc::new(steamgriddb_api::Client::new("dummy")),
-        Arc::new(client),
-        server.base_url(),
-    );
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
 
-    let res = service.get_first_hero_by_game_id(400).await;
+    let res = service
+        .get_best_hero_by_game_id(400, &AssetFilter::default(), &Selection::default())
+        .await;
     assert!(res.is_err());
     let err = format!("{}", res.unwrap_err());
     assert!(err.contains("success=false"));
 }
+
+// typed errors
+
+#[tokio::test]
+async fn hero_not_found_returns_upstream_status() {
+    let server = MockServer::start();
+
+    let _m = server.mock(|when, then| {
+        when.method(GET)
+            .path("/heroes/game/404")
+            .query_param("limit", "10");
+        then.status(404)
+            .body(r#"{"success":false,"errors":["Game not found"]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
+
+    let res = service
+        .get_best_hero_by_game_id(404, &AssetFilter::default(), &Selection::default())
+        .await;
+    match res {
+        Err(ApiError::UpstreamStatus {
+            endpoint,
+            game_id,
+            status,
+        }) => {
+            assert_eq!(endpoint, Endpoint::Heroes);
+            assert_eq!(game_id, Some(404));
+            assert_eq!(status, 404);
+        }
+        _ => panic!("expected upstream status error"),
+    }
+}
+
+#[tokio::test]
+async fn logo_invalid_json_returns_decode_error() {
+    let server = MockServer::start();
+
+    let _m = server.mock(|when, then| {
+        when.method(GET)
+            .path("/logos/game/13")
+            .query_param("limit", "10");
+        then.status(200).body("not json");
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
+
+    let res = service
+        .get_best_logo_by_game_id(13, &AssetFilter::default(), &Selection::default())
+        .await;
+    assert!(matches!(
+        res,
+        Err(ApiError::Decode {
+            endpoint: Endpoint::Logos,
+            game_id: Some(13),
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn slow_upstream_returns_timeout() {
+    let server = MockServer::start();
+
+    let _m = server.mock(|when, then| {
+        when.method(GET).path("/logos/game/5");
+        then.status(200)
+            .delay(Duration::from_millis(500))
+            .body(r#"{"success":true,"page":1,"total":0,"limit":1,"data":[]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_millis(50))
+        .build()
+        .unwrap();
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
+
+    let res = service
+        .get_best_logo_by_game_id(5, &AssetFilter::default(), &Selection::default())
+        .await;
+    assert!(matches!(res, Err(ApiError::Timeout { .. })));
+}
+
+#[tokio::test]
+async fn search_returns_results() {
+    let server = MockServer::start();
+
+    let _m = server.mock(|when, then| {
+        when.method(GET).path("/search/autocomplete/doom");
+        then.status(200).body(r#"{"success":true,"data":[{"id":1,"name":"DOOM","release_date":742262400,"verified":true,"types":["steam"]}]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
+
+    let res = service.search("doom").await.unwrap();
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0].name, "DOOM");
+}
+
+#[tokio::test]
+async fn grids_success_false_returns_unsuccessful() {
+    let server = MockServer::start();
+
+    let _m = server.mock(|when, then| {
+        when.method(GET).path("/grids/game/77");
+        then.status(200)
+            .body(r#"{"success":false,"errors":["something"]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
+
+    let res = service
+        .fetch_assets_by_game_id(77, &AssetFilter::default(), 0, 15)
+        .await;
+    assert!(matches!(
+        res,
+        Err(ApiError::Unsuccessful {
+            endpoint: Endpoint::Grids,
+            game_id: Some(77),
+        })
+    ));
+}
+
+#[tokio::test]
+async fn grid_filters_are_forwarded() {
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET)
+            .path("/grids/game/5")
+            .query_param("styles", "alternate,blurred")
+            .query_param("dimensions", "600x900")
+            .query_param("mimes", "image/png,image/webp")
+            .query_param("types", "animated")
+            .query_param("nsfw", "any")
+            .query_param("epilepsy", "false")
+            .query_param("page", "0")
+            .query_param("limit", "15");
+        then.status(200).body(r#"{"success":true,"data":[]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
+
+    let filter = AssetFilter {
+        styles: vec!["alternate".into(), "blurred".into()],
+        dimensions: vec!["600x900".into()],
+        mimes: vec!["png".into(), "webp".into()],
+        animated: Some(true),
+        nsfw: Some(Inclusion::Any),
+        humor: None,
+        epilepsy: Some(Inclusion::Exclude),
+    };
+    let res = service
+        .fetch_assets_by_game_id(5, &filter, 0, 15)
+        .await
+        .unwrap();
+
+    assert!(res.data.is_empty());
+    m.assert_hits(1);
+}
+
+#[tokio::test]
+async fn icons_are_listed_with_icon_mimes() {
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET)
+            .path("/icons/game/5")
+            .query_param("mimes", "image/vnd.microsoft.icon,image/png");
+        then.status(200).body(r#"{"success":true,"page":0,"total":1,"limit":50,"data":[{"id":1,"url":"https://example.com/icon.ico","thumb":"thumb","score":0,"style":"official","width":256,"height":256,"nsfw":false,"humor":false,"mime":"image/vnd.microsoft.icon","language":"en","lock":false,"epilepsy":false,"upvotes":0,"downvotes":0,"author":{"name":"","steam64":"","avatar":""}}]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
+
+    let filter = AssetFilter {
+        mimes: vec!["icon".into(), "png".into()],
+        ..AssetFilter::default()
+    };
+    let icons = service
+        .fetch_icons_by_game_id(5, &filter, 0, 15)
+        .await
+        .unwrap();
+
+    assert_eq!(icons.data.len(), 1);
+    assert_eq!(icons.data[0].url, "https://example.com/icon.ico");
+    m.assert_hits(1);
+}
+
+// pagination
+
+fn grid(id: u32) -> String {
+    format!(
+        r#"{{"id":{},"url":"https://example.com/{}.png","thumb":"thumb","score":0,"style":"alternate","width":600,"height":900,"nsfw":false,"humor":false,"mime":"image/png","language":"en","lock":false,"epilepsy":false,"upvotes":0,"downvotes":0,"author":{{"name":"","steam64":"","avatar":null}}}}"#,
+        id, id
+    )
+}
+
+fn grids_page(ids: std::ops::Range<u32>, total: u32) -> String {
+    let data: Vec<String> = ids.map(grid).collect();
+    format!(
+        r#"{{"success":true,"page":0,"total":{},"limit":0,"data":[{}]}}"#,
+        total,
+        data.join(",")
+    )
+}
+
+#[tokio::test]
+async fn aligned_window_is_a_single_upstream_page() {
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET)
+            .path("/grids/game/8")
+            .query_param("page", "2")
+            .query_param("limit", "10");
+        then.status(200).body(grids_page(20..30, 95));
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
+
+    let res = service
+        .fetch_assets_by_game_id(8, &AssetFilter::default(), 20, 10)
+        .await
+        .unwrap();
+
+    let ids: Vec<u32> = res.data.iter().map(|g| g.id).collect();
+    assert_eq!(ids, (20..30).collect::<Vec<_>>());
+    assert_eq!(res.total, 95);
+    m.assert_hits(1);
+}
+
+#[tokio::test]
+async fn unaligned_window_spans_two_upstream_pages() {
+    let server = MockServer::start();
+
+    let first = server.mock(|when, then| {
+        when.method(GET)
+            .path("/grids/game/8")
+            .query_param("page", "0")
+            .query_param("limit", "10");
+        then.status(200).body(grids_page(0..10, 13));
+    });
+    let second = server.mock(|when, then| {
+        when.method(GET)
+            .path("/grids/game/8")
+            .query_param("page", "1")
+            .query_param("limit", "10");
+        then.status(200).body(grids_page(10..13, 13));
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
+
+    let res = service
+        .fetch_assets_by_game_id(8, &AssetFilter::default(), 5, 10)
+        .await
+        .unwrap();
+
+    let ids: Vec<u32> = res.data.iter().map(|g| g.id).collect();
+    assert_eq!(ids, (5..13).collect::<Vec<_>>());
+    assert_eq!(res.total, 13);
+    first.assert_hits(1);
+    second.assert_hits(1);
+}
+
+// request coalescing
+
+fn hero(id: u32, style: &str, language: &str, score: u32, upvotes: u32) -> String {
+    format!(
+        r#"{{"id":{},"url":"https://example.com/{}.png","thumb":"thumb","score":{},"style":"{}","width":1920,"height":620,"nsfw":false,"humor":false,"mime":"image/png","language":"{}","lock":false,"epilepsy":false,"upvotes":{},"downvotes":0,"author":{{"name":"","steam64":"","avatar":null}}}}"#,
+        id, id, score, style, language, upvotes
+    )
+}
+
+#[tokio::test]
+async fn best_hero_is_picked_from_the_candidates() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET)
+            .path("/heroes/game/8")
+            .query_param("page", "0")
+            .query_param("limit", "5");
+        then.status(200).body(format!(
+            r#"{{"success":true,"page":0,"total":4,"limit":5,"data":[{},{},{},{}]}}"#,
+            hero(1, "alternate", "en", 1, 9),
+            hero(2, "blurred", "de", 5, 0),
+            hero(3, "alternate", "en", 7, 2),
+            hero(4, "material", "en", 3, 1)
+        ));
+    });
+
+    let client = Arc::new(Client::new());
+    let service = SteamgriddbService::new(client, server.base_url());
+    let filter = AssetFilter::default();
+    let best = |selection: Selection| {
+        let service = service.clone();
+        let filter = filter.clone();
+        async move {
+            service
+                .get_best_hero_by_game_id(8, &filter, &selection)
+                .await
+                .unwrap()
+                .unwrap()
+        }
+    };
+    let base = Selection {
+        candidates: 5,
+        ..Selection::default()
+    };
+
+    assert_eq!(best(base.clone()).await, "https://example.com/3.png");
+    assert_eq!(
+        best(Selection {
+            rank_by: Ranking::Upvotes,
+            ..base.clone()
+        })
+        .await,
+        "https://example.com/1.png"
+    );
+    assert_eq!(
+        best(Selection {
+            rank_by: Ranking::First,
+            ..base.clone()
+        })
+        .await,
+        "https://example.com/1.png"
+    );
+    assert_eq!(
+        best(Selection {
+            styles: vec!["material".into(), "blurred".into()],
+            ..base.clone()
+        })
+        .await,
+        "https://example.com/4.png"
+    );
+    assert_eq!(
+        best(Selection {
+            language: Some("de".into()),
+            ..base.clone()
+        })
+        .await,
+        "https://example.com/2.png"
+    );
+    m.assert_hits(5);
+}
+
+#[tokio::test]
+async fn animated_heroes_are_only_picked_without_a_static_one() {
+    let server = MockServer::start();
+    let static_only = server.mock(|when, then| {
+        when.method(GET)
+            .path("/heroes/game/9")
+            .query_param("types", "static");
+        then.status(200)
+            .body(r#"{"success":true,"page":0,"total":0,"limit":10,"data":[]}"#);
+    });
+    let any = server.mock(|when, then| {
+        when.method(GET).path("/heroes/game/9").matches(|req| {
+            !req.query_params
+                .iter()
+                .flatten()
+                .any(|(name, _)| name == "types")
+        });
+        then.status(200).body(format!(
+            r#"{{"success":true,"page":0,"total":1,"limit":10,"data":[{}]}}"#,
+            hero(1, "alternate", "en", 0, 0)
+        ));
+    });
+
+    let client = Arc::new(Client::new());
+    let service = SteamgriddbService::new(client, server.base_url());
+    let selection = Selection {
+        prefer_static: true,
+        ..Selection::default()
+    };
+    let res = service
+        .get_best_hero_by_game_id(9, &AssetFilter::default(), &selection)
+        .await
+        .unwrap();
+
+    assert_eq!(res.as_deref(), Some("https://example.com/1.png"));
+    static_only.assert_hits(1);
+    any.assert_hits(1);
+}
+
+#[tokio::test]
+async fn concurrent_identical_requests_share_one_fetch() {
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET)
+            .path("/heroes/game/7")
+            .query_param("limit", "10");
+        then.status(200).delay(Duration::from_millis(200)).body(r#"{"success":true,"page":1,"total":1,"limit":1,"data":[{"id":1,"url":"https://example.com/hero.png","thumb":"thumb","score":0,"style":"","width":1,"height":1,"nsfw":false,"humor":false,"mime":"image/png","language":"","lock":false,"epilepsy":false,"upvotes":0,"downvotes":0,"author":{"name":"","steam64":"","avatar":""}}]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
+    let other = service.clone();
+
+    let filter = AssetFilter::default();
+    let selection = Selection::default();
+    let (a, b) = tokio::join!(
+        service.get_best_hero_by_game_id(7, &filter, &selection),
+        other.get_best_hero_by_game_id(7, &filter, &selection)
+    );
+
+    assert_eq!(a.unwrap().unwrap(), "https://example.com/hero.png");
+    assert_eq!(b.unwrap().unwrap(), "https://example.com/hero.png");
+    m.assert_hits(1);
+}
+
+#[tokio::test]
+async fn sequential_requests_are_not_coalesced() {
+    let server = MockServer::start();
+
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/search/autocomplete/zelda");
+        then.status(200).body(r#"{"success":true,"data":[]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url());
+
+    service.search("zelda").await.unwrap();
+    service.search("zelda").await.unwrap();
+
+    m.assert_hits(2);
+}
+
+#[tokio::test]
+async fn fetch_outlives_cancelled_callers_and_releases_its_key() {
+    let limiter = Arc::new(RateLimiter::new(2.0, 1, 2));
+    limiter.acquire(Endpoint::Logos, Some(1)).await.unwrap();
+
+    let flights = SingleFlight::<u32>::default();
+    let key = RequestKey::new(Endpoint::Logos, Some(1), "");
+    for _ in 0..2 {
+        let limiter = limiter.clone();
+        let fetch = async move { limiter.acquire(Endpoint::Logos, Some(1)).await.map(|()| 1) };
+        let res =
+            tokio::time::timeout(Duration::from_millis(20), flights.run(key.clone(), fetch)).await;
+        assert!(res.is_err());
+    }
+    // the second caller joined the first fetch instead of queueing its own
+    assert_eq!(limiter.queued(), 1);
+    assert_eq!(flights.len(), 1);
+
+    tokio::time::sleep(Duration::from_millis(700)).await;
+    assert_eq!(limiter.queued(), 0);
+    assert!(flights.is_empty());
+}
+
+#[tokio::test]
+async fn cancelled_lookups_do_not_hold_rate_limiter_slots() {
+    let server = MockServer::start();
+    let m = server.mock(|when, then| {
+        when.method(GET).path("/logos/game/1");
+        then.status(200)
+            .body(r#"{"success":true,"page":0,"total":0,"limit":10,"data":[]}"#);
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let limiter = Arc::new(RateLimiter::new(2.0, 1, 2));
+    let service = SteamgriddbService::new(Arc::new(client), server.base_url())
+        .with_rate_limiter(limiter.clone());
+    limiter.acquire(Endpoint::Logos, Some(1)).await.unwrap();
+
+    let filter = AssetFilter::default();
+    let selection = Selection::default();
+    for _ in 0..2 {
+        let res = tokio::time::timeout(
+            Duration::from_millis(20),
+            service.get_best_logo_by_game_id(1, &filter, &selection),
+        )
+        .await;
+        assert!(res.is_err());
+    }
+
+    tokio::time::sleep(Duration::from_millis(700)).await;
+    assert_eq!(limiter.queued(), 0);
+    m.assert_hits(1);
+
+    // the bucket is empty again, but nothing else is waiting
+    assert!(service
+        .get_best_logo_by_game_id(1, &filter, &selection)
+        .await
+        .unwrap()
+        .is_none());
+    m.assert_hits(2);
+}